@@ -0,0 +1,265 @@
+use crate::options::Overrides;
+use rocksdb::{ColumnFamily, DBCompressionType, IteratorMode, Options, WriteBatch, DB};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Instant;
+
+const COPY_BATCH_BYTES: usize = 4 << 20;
+
+/// A maintenance operation on one column family; everything except reading options needs
+/// the database opened for writing.
+#[derive(Clone, Debug)]
+pub enum AdminOp {
+    Compact { cf: String, start: Option<String>, end: Option<String> },
+    Flush { cf: String },
+    SetOption { cf: String, name: String, value: String },
+    DropCf { cf: String },
+    CreateCf { name: String, options: String },
+    CopyCf { cf: String, target: String },
+}
+
+impl AdminOp {
+    /// Builds the operation to confirm from the filled-in fields; empty range bounds are open.
+    /// The error says which field still needs to be filled in.
+    pub fn from_fields(cf: &str, action: AdminAction, fields: &[(&'static str, String)]) -> Result<AdminOp, String> {
+        let cf = cf.to_string();
+        let field = |i: usize| fields.get(i).map(|(_, v)| v.clone()).filter(|v| !v.is_empty());
+        let required = |i: usize| field(i).ok_or_else(|| format!("enter the {}", fields.get(i).map_or("value", |(label, _)| label)));
+        Ok(match action {
+            AdminAction::CompactCf => AdminOp::Compact { cf, start: None, end: None },
+            AdminAction::CompactRange => AdminOp::Compact { cf, start: field(0), end: field(1) },
+            AdminAction::Flush => AdminOp::Flush { cf },
+            AdminAction::SetOption => AdminOp::SetOption { cf, name: required(0)?, value: required(1)? },
+            // Dropping only goes ahead once the column family's name has been typed
+            AdminAction::DropCf => match field(0) {
+                Some(typed) if typed == cf => AdminOp::DropCf { cf },
+                _ => return Err(format!("type {} exactly to drop it", cf)),
+            },
+            AdminAction::CreateCf => AdminOp::CreateCf { name: required(0)?, options: field(1).unwrap_or_default() },
+            AdminAction::CopyCf => AdminOp::CopyCf { cf, target: required(0)? },
+            AdminAction::ShowOptions => return Err("show options has nothing to confirm".to_string()),
+        })
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            AdminOp::Compact { cf, start: None, end: None } => format!("compact the whole column family {}", cf),
+            AdminOp::Compact { cf, start, end } => format!(
+                "compact {} from {} to {}",
+                cf,
+                start.as_deref().unwrap_or("the first key"),
+                end.as_deref().unwrap_or("the last key")
+            ),
+            AdminOp::Flush { cf } => format!("flush the memtables of {}", cf),
+            AdminOp::SetOption { cf, name, value } => format!("set {}={} on {}", name, value, cf),
+            AdminOp::DropCf { cf } => format!("drop the column family {} and all of its data", cf),
+            AdminOp::CreateCf { name, options } if options.is_empty() => format!("create the column family {}", name),
+            AdminOp::CreateCf { name, options } => format!("create the column family {} with {}", name, options),
+            AdminOp::CopyCf { cf, target } => format!("copy {} into the new column family {}", cf, target),
+        }
+    }
+
+    pub fn run(&self, db_path: &str, overrides: &Overrides, progress: &AtomicU64) -> Result<String, String> {
+        let mut db = crate::data::open_writable(db_path, overrides).map_err(|e| format!("cannot open {} for writing: {}", db_path, e))?;
+        match self {
+            AdminOp::Compact { cf, start, end } => {
+                db.compact_range_cf(cf_handle(&db, cf)?, start.as_deref(), end.as_deref());
+                Ok(format!("compacted {}", cf))
+            }
+            AdminOp::Flush { cf } => {
+                db.flush_cf(cf_handle(&db, cf)?).map_err(|e| e.to_string())?;
+                Ok(format!("flushed {}", cf))
+            }
+            AdminOp::SetOption { cf, name, value } => {
+                db.set_options_cf(cf_handle(&db, cf)?, &[(name, value)]).map_err(|e| e.to_string())?;
+                // RocksDB records it in a new OPTIONS file, but the next process to open the database applies its own options
+                Ok(format!("set {}={} on {}", name, value, cf))
+            }
+            AdminOp::DropCf { cf } => {
+                db.drop_cf(cf).map_err(|e| e.to_string())?;
+                Ok(format!("dropped {}", cf))
+            }
+            AdminOp::CreateCf { name, options } => {
+                db.create_cf(name, &parse_cf_options(options)?).map_err(|e| e.to_string())?;
+                Ok(format!("created {}", name))
+            }
+            AdminOp::CopyCf { cf, target } => {
+                // The copy keeps the source's comparator and merge operator, so its keys sort and merge the same way
+                db.create_cf(target, &crate::options::cf_options(db_path, cf, overrides)?).map_err(|e| e.to_string())?;
+                match copy_cf(&db, cf_handle(&db, cf)?, cf_handle(&db, target)?, progress) {
+                    Ok(copied) => Ok(format!("copied {} keys from {} into {}", copied, cf, target)),
+                    Err(e) => {
+                        // A partial copy is not left behind to be mistaken for a complete one
+                        let dropped = db.drop_cf(target).err().map(|d| format!("; dropping {} failed too: {}", target, d));
+                        Err(format!("{}{}", e, dropped.unwrap_or_default()))
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn cf_handle<'a>(db: &'a DB, cf: &str) -> Result<&'a ColumnFamily, String> {
+    db.cf_handle(cf).ok_or_else(|| format!("no column family {}", cf))
+}
+
+/// Copies every key of `from` into `to` in write batches of at most `COPY_BATCH_BYTES`.
+fn copy_cf(db: &DB, from: &ColumnFamily, to: &ColumnFamily, progress: &AtomicU64) -> Result<u64, String> {
+    let mut batch = WriteBatch::default();
+    let mut copied = 0;
+    for item in db.iterator_cf(from, IteratorMode::Start) {
+        let (key, value) = item.map_err(|e| e.to_string())?;
+        batch.put_cf(to, key, value);
+        copied += 1;
+        if batch.size_in_bytes() >= COPY_BATCH_BYTES {
+            db.write(std::mem::take(&mut batch)).map_err(|e| e.to_string())?;
+            progress.store(copied, Ordering::Relaxed);
+        }
+    }
+    if !batch.is_empty() {
+        db.write(batch).map_err(|e| e.to_string())?;
+    }
+    Ok(copied)
+}
+
+/// Builds column family options from `name=value` pairs separated by `;`, for the options
+/// that matter most when prototyping a layout.
+pub fn parse_cf_options(spec: &str) -> Result<Options, String> {
+    let mut opts = Options::default();
+    for pair in spec.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+        let (name, value) = pair.split_once('=').ok_or_else(|| format!("expected name=value, got {}", pair))?;
+        let (name, value) = (name.trim(), value.trim());
+        let number = || value.parse::<u64>().map_err(|_| format!("{} expects a number, got {}", name, value));
+        match name {
+            "write_buffer_size" => opts.set_write_buffer_size(number()? as usize),
+            "max_write_buffer_number" => opts.set_max_write_buffer_number(number()? as i32),
+            "target_file_size_base" => opts.set_target_file_size_base(number()?),
+            "max_bytes_for_level_base" => opts.set_max_bytes_for_level_base(number()?),
+            "level0_file_num_compaction_trigger" => opts.set_level_zero_file_num_compaction_trigger(number()? as i32),
+            "num_levels" => opts.set_num_levels(number()? as i32),
+            "disable_auto_compactions" => opts.set_disable_auto_compactions(value == "true"),
+            "compression" => opts.set_compression_type(match value {
+                "none" => DBCompressionType::None,
+                "snappy" => DBCompressionType::Snappy,
+                "zlib" => DBCompressionType::Zlib,
+                "bz2" => DBCompressionType::Bz2,
+                "lz4" => DBCompressionType::Lz4,
+                "lz4hc" => DBCompressionType::Lz4hc,
+                "zstd" => DBCompressionType::Zstd,
+                other => return Err(format!("unknown compression {}", other)),
+            }),
+            other => return Err(format!("unsupported option {}", other)),
+        }
+    }
+    Ok(opts)
+}
+
+/// Reads the options of a column family from the newest `OPTIONS-*` file of the database.
+pub fn read_cf_options(db_path: &str, cf: &str) -> Result<Vec<(String, String)>, String> {
+    let path = crate::options::latest_options_file(db_path).ok_or("no OPTIONS file in the database directory")?;
+    let sections = crate::options::read_cf_sections(&path)?;
+    Ok(sections.into_iter().find(|(name, _)| name == cf).map(|(_, options)| options).unwrap_or_default())
+}
+
+/// An admin operation running on a background thread.
+pub struct AdminTask {
+    pub description: String,
+    pub started: Instant,
+    /// Keys processed so far, for operations that can tell.
+    pub progress: Arc<AtomicU64>,
+    rx: mpsc::Receiver<Result<String, String>>,
+}
+
+impl AdminTask {
+    pub fn spawn(op: AdminOp, db_path: &str, overrides: &Overrides) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let db_path = db_path.to_string();
+        let overrides = overrides.clone();
+        let description = op.describe();
+        let progress = Arc::new(AtomicU64::new(0));
+        let task_progress = progress.clone();
+        thread::spawn(move || {
+            let _ = tx.send(op.run(&db_path, &overrides, &task_progress));
+        });
+        Self { description, started: Instant::now(), progress, rx }
+    }
+
+    pub fn poll(&self) -> Option<Result<String, String>> {
+        self.rx.try_recv().ok()
+    }
+
+    /// A spinner frame derived from the elapsed time, so it moves on every redraw.
+    pub fn spinner(&self) -> char {
+        const FRAMES: [char; 4] = ['|', '/', '-', '\\'];
+        FRAMES[(self.started.elapsed().as_millis() / 150) as usize % FRAMES.len()]
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AdminAction {
+    CompactCf,
+    CompactRange,
+    Flush,
+    ShowOptions,
+    SetOption,
+    DropCf,
+    CreateCf,
+    CopyCf,
+}
+
+impl AdminAction {
+    pub const ALL: [AdminAction; 8] = [
+        AdminAction::CompactCf,
+        AdminAction::CompactRange,
+        AdminAction::Flush,
+        AdminAction::ShowOptions,
+        AdminAction::SetOption,
+        AdminAction::DropCf,
+        AdminAction::CreateCf,
+        AdminAction::CopyCf,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AdminAction::CompactCf => "compact column family",
+            AdminAction::CompactRange => "compact key range",
+            AdminAction::Flush => "flush memtables",
+            AdminAction::ShowOptions => "show options",
+            AdminAction::SetOption => "set option",
+            AdminAction::DropCf => "drop column family",
+            AdminAction::CreateCf => "create column family",
+            AdminAction::CopyCf => "copy into a new column family",
+        }
+    }
+}
+
+pub enum AdminStage {
+    Menu,
+    /// Text fields to fill in before confirming, as (label, value); Tab moves between them.
+    Input { action: AdminAction, fields: Vec<(&'static str, String)>, field: usize },
+    Confirm(AdminOp),
+}
+
+/// The admin menu popup: a column family, an action and the stage of filling it in.
+pub struct AdminMenu {
+    pub cf_names: Vec<String>,
+    pub cf_index: usize,
+    pub action_index: usize,
+    pub stage: AdminStage,
+}
+
+impl AdminMenu {
+    pub fn new(db_path: &str) -> Self {
+        let cf_names = rocksdb::DB::list_cf(&rocksdb::Options::default(), db_path).unwrap_or_else(|_| vec!["default".to_string()]);
+        Self { cf_names, cf_index: 0, action_index: 0, stage: AdminStage::Menu }
+    }
+
+    pub fn cf(&self) -> &str {
+        &self.cf_names[self.cf_index]
+    }
+
+    pub fn action(&self) -> AdminAction {
+        AdminAction::ALL[self.action_index]
+    }
+}
@@ -0,0 +1,149 @@
+use crate::options::{KeyOrder, Overrides};
+use rocksdb::{Direction, IteratorMode};
+use std::collections::BTreeMap;
+use std::sync::mpsc;
+use std::thread;
+
+const LARGEST_VALUES: usize = 5;
+
+/// Key/value size totals for one record type, accumulated while the loader iterates.
+#[derive(Clone, Debug, Default)]
+pub struct TypeStats {
+    pub count: usize,
+    pub key_bytes: u64,
+    pub value_bytes: u64,
+    /// The biggest values seen, as (key, value size), largest first.
+    pub largest: Vec<(String, usize)>,
+}
+
+impl TypeStats {
+    pub fn add(&mut self, key: &str, key_len: usize, value_len: usize) {
+        self.count += 1;
+        self.key_bytes += key_len as u64;
+        self.value_bytes += value_len as u64;
+        if self.largest.len() < LARGEST_VALUES || self.largest.last().is_some_and(|(_, size)| value_len > *size) {
+            let pos = self.largest.iter().position(|(_, size)| value_len > *size).unwrap_or(self.largest.len());
+            self.largest.insert(pos, (key.to_string(), value_len));
+            self.largest.truncate(LARGEST_VALUES);
+        }
+    }
+
+    pub fn avg_key_bytes(&self) -> u64 {
+        self.key_bytes / self.count.max(1) as u64
+    }
+
+    pub fn avg_value_bytes(&self) -> u64 {
+        self.value_bytes / self.count.max(1) as u64
+    }
+}
+
+/// Value size histogram and key segment distribution for one record type.
+#[derive(Clone, Debug, Default)]
+pub struct ChartData {
+    pub scanned: usize,
+    /// Power-of-two value size buckets as (upper bound in bytes, count).
+    pub size_buckets: Vec<(u64, u64)>,
+    /// Key counts per segment value, in key order.
+    pub segment_counts: Vec<(String, u64)>,
+}
+
+/// Which record type and key segment the charts are computed for.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChartQuery {
+    pub record_type: String,
+    /// Index into the `:`-separated key; 0 is the record type itself.
+    pub segment: usize,
+    /// Only the first `prefix_len` characters of the segment are grouped on, 0 for all of it
+    /// (e.g. 10 turns an ISO timestamp into a per-day distribution).
+    pub prefix_len: usize,
+}
+
+impl ChartQuery {
+    fn group<'a>(&self, key: &'a str) -> Option<&'a str> {
+        let segment = key.split(':').nth(self.segment)?;
+        match segment.char_indices().nth(self.prefix_len) {
+            Some((end, _)) if self.prefix_len > 0 => Some(&segment[..end]),
+            _ => Some(segment),
+        }
+    }
+}
+
+/// Streams over the keys of `query.record_type` and buckets value sizes and key segments
+/// without keeping the records in memory. Under bytewise order the scan starts at the type
+/// prefix and ends after its last key; other orders do not keep a prefix together, so the
+/// whole column family is scanned and filtered.
+pub fn compute_charts(db_path: &str, overrides: &Overrides, query: &ChartQuery) -> Result<ChartData, String> {
+    let (db, _) = crate::data::open_read_only(db_path, overrides).map_err(|e| e.to_string())?;
+    let bytewise = crate::options::key_order(db_path, "default", overrides)? == KeyOrder::Bytewise;
+    let prefix = query.record_type.as_bytes();
+    let mut buckets: BTreeMap<u32, u64> = BTreeMap::new();
+    let mut segments: BTreeMap<String, u64> = BTreeMap::new();
+    let mut scanned = 0;
+
+    let mode = if bytewise { IteratorMode::From(prefix, Direction::Forward) } else { IteratorMode::Start };
+    for item in db.iterator(mode) {
+        let (key_bytes, value_bytes) = item.map_err(|e| e.to_string())?;
+        if !key_bytes.starts_with(prefix) {
+            if bytewise {
+                break;
+            }
+            continue;
+        }
+        let key = String::from_utf8_lossy(&key_bytes);
+        if key.split(':').next() != Some(query.record_type.as_str()) {
+            continue;
+        }
+        scanned += 1;
+        let bucket = (value_bytes.len() as u64).max(1).next_power_of_two().trailing_zeros();
+        *buckets.entry(bucket).or_default() += 1;
+        if let Some(group) = query.group(&key) {
+            *segments.entry(group.to_string()).or_default() += 1;
+        }
+    }
+
+    let size_buckets = match (buckets.keys().next(), buckets.keys().last()) {
+        (Some(&first), Some(&last)) => (first..=last).map(|b| (1u64 << b, buckets.get(&b).copied().unwrap_or(0))).collect(),
+        _ => Vec::new(),
+    };
+    Ok(ChartData { scanned, size_buckets, segment_counts: segments.into_iter().collect() })
+}
+
+/// The charts view: its query and the result of the background scan, once it arrives.
+pub struct ChartsState {
+    pub query: ChartQuery,
+    pub data: Option<Result<ChartData, String>>,
+    rx: Option<mpsc::Receiver<Result<ChartData, String>>>,
+}
+
+impl ChartsState {
+    pub fn new(record_type: &str) -> Self {
+        Self {
+            query: ChartQuery { record_type: record_type.to_string(), segment: 1, prefix_len: 0 },
+            data: None,
+            rx: None,
+        }
+    }
+
+    /// Starts a new scan on a background thread; the previous result stays visible until it finishes.
+    pub fn refresh(&mut self, db_path: &str, overrides: &Overrides) {
+        let (tx, rx) = mpsc::channel();
+        let db_path = db_path.to_string();
+        let overrides = overrides.clone();
+        let query = self.query.clone();
+        thread::spawn(move || {
+            let _ = tx.send(compute_charts(&db_path, &overrides, &query));
+        });
+        self.rx = Some(rx);
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.rx.is_some()
+    }
+
+    pub fn poll(&mut self) {
+        if let Some(result) = self.rx.as_ref().and_then(|rx| rx.try_recv().ok()) {
+            self.data = Some(result);
+            self.rx = None;
+        }
+    }
+}
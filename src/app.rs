@@ -1,5 +1,22 @@
+use crate::analytics::ChartsState;
+use crate::clipboard::CopyTarget;
 use crate::data::{DataManager, PaginatedDataLoader};
-use crate::models::Record;
+use crate::hexdump::HexView;
+use crate::layout::{ColumnChooser, LayoutStore};
+use crate::models::{Record, RuleSet, SortValue};
+use crate::timestamps::{DisplayZone, TimeFormat};
+use crate::config::{Config, Theme};
+use crate::keymap::{normalize_key, Input, Keymap};
+use crate::options::{OpenMode, Overrides};
+use crate::profiles::{PickerChoice, ProfilePicker};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crate::admin::{AdminAction, AdminMenu, AdminOp, AdminStage, AdminTask};
+use crate::backup::{BackupsState, PathAction};
+use crate::diff::{DiffState, RecordDiff};
+use crate::wal::{WalFile, WalRow};
+use rocksdb::LiveFile;
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::time::Instant;
 
 #[derive(Clone, Debug, PartialEq)]
@@ -10,7 +27,65 @@ pub enum Focus {
     Pages,
 }
 
+pub const COLUMN_SPACING: u16 = 3;
+
+/// The formatted cells of the selected table's rows, so timestamps are recognised and formatted
+/// once per load (or column change) rather than on every frame.
+#[derive(Default)]
+pub struct RowCache {
+    record_type: String,
+    generation: u64,
+    headers: Vec<String>,
+    formats: Vec<TimeFormat>,
+    zone: DisplayZone,
+    /// Cells by record key.
+    rows: HashMap<String, Vec<String>>,
+}
+
+/// The cells of a table's rows: from the row cache when it was built for the same columns and
+/// data, otherwise formatted on the spot.
+pub struct RowCells<'a> {
+    cached: Option<&'a HashMap<String, Vec<String>>>,
+    headers: &'a [String],
+    formats: &'a [TimeFormat],
+    zone: DisplayZone,
+}
+
+impl<'a> RowCells<'a> {
+    pub fn get(&self, record: &Record) -> Cow<'a, [String]> {
+        match self.cached.and_then(|rows| rows.get(&record.key)) {
+            Some(row) => Cow::Borrowed(row),
+            None => Cow::Owned(record.to_table_row(self.headers, self.formats, self.zone)),
+        }
+    }
+}
+
+pub struct ColumnLayout {
+    pub columns: Vec<usize>,
+    pub widths: Vec<u16>,
+    pub hidden_left: usize,
+    pub hidden_right: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum View {
+    Browse,
+    Stats,
+    Charts,
+    Sst,
+    Wal,
+    Diff,
+    Backups,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SortKey {
+    pub column: String,
+    pub ascending: bool,
+}
+
 pub struct App {
+    pub db_path: String,
     pub data_manager: DataManager<PaginatedDataLoader>,
     pub input: String,
     pub scroll_y: u16,
@@ -18,27 +93,83 @@ pub struct App {
     pub selected_table: Option<String>,
     pub selected_row: Option<usize>,
     pub show_raw_data: Option<String>,
+    /// What the text popup shows: a decoded value, a WAL op, options or a message.
+    pub popup_title: &'static str,
+    pub raw_scroll: u16,
+    pub raw_scroll_x: u16,
+    pub raw_wrap: bool,
+    pub raw_maximized: bool,
+    pub raw_page_height: u16,
+    /// Whether the records view shows the selected row's value in a panel beside the table.
+    pub detail_panel: bool,
+    /// The panel's share of the width, in percent.
+    pub detail_width: u16,
+    pub detail_scroll: u16,
+    /// The key shown in the panel, so the scroll resets when the selection moves.
+    pub detail_key: Option<String>,
+    /// Left edge of the panel as last drawn, 0 while it is hidden.
+    pub detail_x: u16,
+    pub hex_view: Option<HexView>,
+    pub status_message: Option<String>,
+    pub copy_menu: bool,
     pub should_quit: bool,
     pub last_click: Option<(Instant, String, usize)>,
     pub table_select_index: usize,
-    pub sort_column: Option<usize>,
-    pub sort_ascending: bool,
+    pub sort_keys: Vec<SortKey>,
     pub current_page: usize,
     pub page_focus: bool,
     pub rows_per_page: usize,
+    pub col_offset: usize,
+    pub table_width: u16,
+    pub layouts: LayoutStore,
+    pub column_chooser: Option<ColumnChooser>,
+    pub view: View,
+    pub stats_cf_index: usize,
+    pub stats_scroll: u16,
+    pub charts: Option<ChartsState>,
+    pub sst_files: Option<Result<Vec<LiveFile>, String>>,
+    pub sst_index: usize,
+    pub wal: Option<Result<Vec<WalFile>, String>>,
+    pub wal_index: usize,
+    pub diff: Option<DiffState>,
+    /// Keys of the rows marked for comparison in the selected table, oldest first.
+    pub marked: Vec<String>,
+    /// The table and page the marks were made on; they are dropped when either changes.
+    marked_page: Option<(String, usize)>,
+    pub record_diff: Option<RecordDiff>,
+    pub row_cache: RowCache,
+    pub backups: Option<BackupsState>,
+    /// The comparator, merge operator, TTL and mode this tab opens its database with.
+    pub overrides: Overrides,
+    /// Decoders and key schemas by record type, from the config file.
+    pub rule_set: RuleSet,
+    /// The zone timestamps are shown and filter bounds are read in.
+    pub zone: DisplayZone,
+    pub admin_menu: Option<AdminMenu>,
+    pub admin_task: Option<AdminTask>,
+    pub theme: Theme,
+    /// The key preset and bindings from the `[keys]` section of the config file.
+    pub keymap: Keymap,
+    pub config: Config,
+    /// The profile the database was opened with, if any.
+    pub profile: Option<String>,
+    pub profile_picker: Option<ProfilePicker>,
 }
 
 impl App {
-    pub fn new(db_path: &str) -> Self {
-    let loader = PaginatedDataLoader::new(db_path.to_string());
+    pub fn new(db_path: &str, overrides: Overrides, rule_set: RuleSet) -> Self {
+        let loader = PaginatedDataLoader::new(db_path.to_string(), overrides.clone(), rule_set.clone());
         let mut data_manager = DataManager::new(loader);
-        data_manager.start_background_loading();
-        if let Ok(initial_records) = data_manager.rx.recv() {
-            data_manager.records = initial_records;
-            data_manager.collect_headers();
+        // Without a database (the startup picker) there is nothing to load until one is chosen
+        if !db_path.is_empty() {
+            data_manager.start_background_loading();
+            if let Ok(initial) = data_manager.rx.recv() {
+                data_manager.apply_loaded(initial);
+            }
         }
 
         Self {
+            db_path: db_path.to_string(),
             data_manager,
             input: String::new(),
             scroll_y: 0,
@@ -46,17 +177,446 @@ impl App {
             selected_table: None,
             selected_row: None,
             show_raw_data: None,
+            popup_title: "message",
+            raw_scroll: 0,
+            raw_scroll_x: 0,
+            raw_wrap: true,
+            raw_maximized: false,
+            raw_page_height: 1,
+            detail_panel: false,
+            detail_width: 40,
+            detail_scroll: 0,
+            detail_key: None,
+            detail_x: 0,
+            hex_view: None,
+            status_message: None,
+            copy_menu: false,
             last_click: None,
             table_select_index: 0,
             should_quit: false,
-            sort_column: None,
-            sort_ascending: true,
+            sort_keys: Vec::new(),
             current_page: 0,
             page_focus: false,
             rows_per_page: 20,
+            col_offset: 0,
+            table_width: 80,
+            layouts: LayoutStore::load(),
+            column_chooser: None,
+            view: View::Browse,
+            stats_cf_index: 0,
+            stats_scroll: 0,
+            charts: None,
+            sst_files: None,
+            sst_index: 0,
+            wal: None,
+            wal_index: 0,
+            diff: None,
+            marked: Vec::new(),
+            marked_page: None,
+            record_diff: None,
+            row_cache: RowCache::default(),
+            backups: None,
+            overrides,
+            rule_set,
+            zone: DisplayZone::Local,
+            admin_menu: None,
+            admin_task: None,
+            theme: Theme::default(),
+            keymap: Keymap::default(),
+            config: Config::default(),
+            profile: None,
+            profile_picker: None,
+        }
+    }
+
+    /// A new app on `db_path` sharing this one's configuration, for switching databases or opening a tab.
+    pub fn open_sibling(&self, db_path: &str, overrides: Overrides, rule_set: RuleSet) -> App {
+        let mut app = App::new(db_path, overrides, rule_set);
+        app.zone = self.zone;
+        app.theme = self.theme.clone();
+        app.keymap = self.keymap.clone();
+        app.layouts.defaults = self.layouts.defaults.clone();
+        app.config = self.config.clone();
+        app.detail_panel = self.detail_panel;
+        app.detail_width = self.detail_width;
+        app
+    }
+
+    /// Another view of the same database, with the same profile and table.
+    pub fn duplicate(&self) -> App {
+        let mut app = self.open_sibling(&self.db_path, self.overrides.clone(), self.rule_set.clone());
+        app.profile = self.profile.clone();
+        if let Some(table) = &self.selected_table && app.data_manager.get_records().contains_key(table) {
+            app.selected_table = Some(table.clone());
+            app.focus = Focus::Table;
+        }
+        app
+    }
+
+    /// A short name for the tab bar: the profile, or the last component of the path.
+    pub fn tab_label(&self) -> String {
+        let database = self.profile.clone().unwrap_or_else(|| {
+            let path = self.db_path.trim_end_matches('/');
+            path.rsplit('/').next().filter(|name| !name.is_empty()).unwrap_or(path).to_string()
+        });
+        match &self.selected_table {
+            Some(table) => format!("{}:{}", database, table),
+            None if database.is_empty() => "(no database)".to_string(),
+            None => database,
+        }
+    }
+
+    pub fn open_profile_picker(&mut self) {
+        self.set_view(View::Browse);
+        self.profile_picker = Some(ProfilePicker::new(&self.config, self.profile.as_deref(), false));
+    }
+
+    /// Opens the chosen profile or path in place of the current database. Settings come from
+    /// the profile and `[defaults]`; command line flags only applied to the first database.
+    pub fn open_choice(&mut self, choice: PickerChoice) -> Result<(), String> {
+        let (profile_name, path) = match choice {
+            PickerChoice::Profile(name) => (Some(name), None),
+            PickerChoice::Path(path) => (None, Some(path)),
+        };
+        let profile = match &profile_name {
+            Some(name) => Some(self.config.profiles.get(name).ok_or_else(|| format!("no profile {}", name))?),
+            None => None,
+        };
+        let settings = profile.map(|p| p.settings.clone()).unwrap_or_default().or(&self.config.defaults);
+        let db_path = path.or_else(|| settings.db_path.clone()).ok_or("the profile has no db_path")?;
+        if !std::path::Path::new(&db_path).is_dir() {
+            return Err(format!("{} is not a directory", db_path));
+        }
+        let zone = settings.tz.as_deref().map(crate::timestamps::parse_zone).transpose()?.unwrap_or_default();
+
+        let mut next = self.open_sibling(&db_path, settings.overrides(), self.config.record_rules(profile));
+        next.zone = zone;
+        next.profile = profile_name;
+        *self = next;
+        Ok(())
+    }
+
+    /// Set by `--writable` or `mode = "writable"`; the admin menu and backups are only offered then.
+    pub fn writable(&self) -> bool {
+        self.overrides.mode == OpenMode::Writable
+    }
+
+    /// Whether keys currently go into a text field: the filter, or a path, search or admin prompt.
+    fn typing(&self) -> bool {
+        if let Some(picker) = &self.profile_picker {
+            picker.path_input.is_some()
+        } else if self.record_diff.is_some() {
+            false
+        } else if let Some(view) = &self.hex_view {
+            view.search_input.is_some()
+        } else if self.show_raw_data.is_some() {
+            false
+        } else if let Some(chooser) = &self.column_chooser {
+            chooser.width_input.is_some()
+        } else if let Some(menu) = &self.admin_menu {
+            matches!(menu.stage, AdminStage::Input { .. })
+        } else if self.copy_menu {
+            false
+        } else {
+            match self.view {
+                View::Browse => self.focus == Focus::Input,
+                View::Backups => self.backups.as_ref().is_some_and(|b| b.prompt.is_some()),
+                _ => false,
+            }
         }
     }
 
+    /// Translates a key through the keymap into what it does; nothing while it starts a longer
+    /// key sequence. Plain characters typed into a text field are text, whatever they are bound to.
+    pub fn map_key(&mut self, key: KeyEvent) -> Vec<Input> {
+        let plain_char = matches!(key.code, KeyCode::Char(_)) && (key.modifiers - KeyModifiers::SHIFT).is_empty();
+        if plain_char && self.typing() {
+            self.keymap.reset();
+            return vec![Input { action: None, key }];
+        }
+        let pressed = normalize_key(key.code, key.modifiers);
+        self.keymap.resolve(pressed).into_iter().map(|(action, binding)| {
+            // Keys passed on from a broken-off sequence are rebuilt from their binding
+            let key = if binding == pressed { key } else { KeyEvent::new(binding.0, binding.1) };
+            Input { action, key }
+        }).collect()
+    }
+
+    pub fn set_view(&mut self, view: View) {
+        self.data_manager.set_stats_wanted(view == View::Stats);
+        self.view = view;
+    }
+
+    /// Opens the charts view for the selected record type, or the one highlighted in the picker.
+    pub fn open_charts(&mut self) {
+        let record_type = self.selected_table.clone().or_else(|| {
+            let mut types: Vec<String> = self.data_manager.get_records().keys().cloned().collect();
+            types.sort();
+            types.get(self.table_select_index).cloned()
+        });
+        let Some(record_type) = record_type else {
+            self.status_message = Some("select a record type first".to_string());
+            return;
+        };
+        if self.charts.as_ref().is_none_or(|c| c.query.record_type != record_type) {
+            let mut charts = ChartsState::new(&record_type);
+            charts.refresh(&self.db_path, &self.overrides);
+            self.charts = Some(charts);
+        }
+        self.set_view(View::Charts);
+    }
+
+    pub fn open_sst_files(&mut self) {
+        self.sst_files = Some(crate::sst::live_files(&self.db_path, &self.overrides));
+        self.set_view(View::Sst);
+    }
+
+    /// Reads the WAL files and puts the cursor on the most recent write.
+    pub fn open_wal(&mut self) {
+        let wal = crate::wal::read_wal_files(&self.db_path);
+        self.wal_index = wal.as_ref().map_or(0, |files| crate::wal::rows(files).len().saturating_sub(1));
+        self.wal = Some(wal);
+        self.set_view(View::Wal);
+    }
+
+    /// Shows the selected WAL op with its value decoded like a table record.
+    pub fn open_wal_op(&mut self) {
+        let Some(Ok(files)) = &self.wal else { return };
+        let Some(WalRow::Op(f, b, o)) = crate::wal::rows(files).get(self.wal_index).copied() else { return };
+        let batch = &files[f].batches[b];
+        let op = &batch.ops[o];
+        let key = String::from_utf8_lossy(&op.key).to_string();
+        let sequence = batch.op_sequences()[o].map_or("-".to_string(), |s| s.to_string());
+        let mut text = format!("{} {}\nsequence: {}\ncolumn family: {}\nfile: {}\n", op.kind.label(), key, sequence, op.column_family, files[f].name);
+        if let Some(value) = &op.value {
+            text.push_str(&format!("\n{}", crate::models::deserialize_record(&self.rule_set, &key, value).pretty_value()));
+        }
+        self.open_text_popup("WAL op", text);
+    }
+
+    /// Takes a checkpoint next to the database without asking, for use right before an edit.
+    pub fn quick_checkpoint(&mut self) {
+        let target = crate::backup::default_checkpoint_dir(&self.db_path);
+        self.status_message = Some(match crate::backup::create_checkpoint(&self.db_path, &target, &self.overrides) {
+            Ok(()) => format!("checkpoint created in {}", target),
+            Err(e) => format!("checkpoint failed: {}", e),
+        });
+    }
+
+    pub fn open_backups(&mut self) {
+        let backup_dir = self.backups.as_ref().map_or_else(|| crate::backup::default_backup_dir(&self.db_path), |b| b.backup_dir.clone());
+        self.backups = Some(BackupsState::new(backup_dir));
+        self.set_view(View::Backups);
+    }
+
+    pub fn create_backup(&mut self) {
+        if !self.writable() {
+            self.status_message = Some("backups need --writable".to_string());
+            return;
+        }
+        let Some(backups) = self.backups.as_mut() else { return };
+        self.status_message = Some(match crate::backup::create_backup(&self.db_path, &backups.backup_dir, &self.overrides) {
+            Ok(id) => format!("backup {} created in {}", id, backups.backup_dir),
+            Err(e) => format!("backup failed: {}", e),
+        });
+        backups.reload();
+    }
+
+    pub fn verify_backup(&mut self) {
+        let Some(backups) = self.backups.as_ref() else { return };
+        let Some(id) = backups.selected_id() else { return };
+        self.status_message = Some(match crate::backup::verify_backup(&backups.backup_dir, id) {
+            Ok(()) => format!("backup {} is intact", id),
+            Err(e) => format!("backup {} failed verification: {}", id, e),
+        });
+    }
+
+    /// Runs the action of the backups screen's path prompt with the entered directory.
+    pub fn submit_backup_prompt(&mut self) {
+        let Some(backups) = self.backups.as_mut() else { return };
+        let Some((action, path)) = backups.prompt.take() else { return };
+        let path = path.trim().to_string();
+        if path.is_empty() {
+            return;
+        }
+        self.status_message = Some(match action {
+            PathAction::Checkpoint => match crate::backup::create_checkpoint(&self.db_path, &path, &self.overrides) {
+                Ok(()) => format!("checkpoint created in {}", path),
+                Err(e) => format!("checkpoint failed: {}", e),
+            },
+            PathAction::Restore(id) => match crate::backup::restore_backup(&backups.backup_dir, id, &path) {
+                Ok(()) => format!("backup {} restored into {}", id, path),
+                Err(e) => format!("restore failed: {}", e),
+            },
+            PathAction::BackupDir => {
+                *backups = BackupsState::new(path);
+                return;
+            }
+        });
+    }
+
+    /// Collects results of background tasks started from the UI.
+    pub fn poll_background(&mut self) {
+        if let Some(charts) = self.charts.as_mut() {
+            charts.poll();
+        }
+        if let Some(task) = &self.admin_task && let Some(result) = task.poll() {
+            self.status_message = Some(match result {
+                Ok(message) => format!("{} in {:.1}s", message, task.started.elapsed().as_secs_f64()),
+                Err(e) => format!("{} failed: {}", task.description, e),
+            });
+            self.admin_task = None;
+        }
+    }
+
+    pub fn open_admin_menu(&mut self) {
+        if !self.writable() {
+            self.status_message = Some("the admin menu needs --writable".to_string());
+            return;
+        }
+        self.admin_menu = Some(AdminMenu::new(&self.db_path));
+    }
+
+    /// Moves the admin menu on from the action list: straight to confirmation, or to the
+    /// fields the action needs first.
+    pub fn choose_admin_action(&mut self) {
+        let Some(menu) = self.admin_menu.as_mut() else { return };
+        let action = menu.action();
+        let fields = match action {
+            AdminAction::CompactRange => {
+                let prefix = self.selected_table.clone().unwrap_or_default();
+                let end = if prefix.is_empty() { String::new() } else { format!("{};", prefix) };
+                let start = if prefix.is_empty() { String::new() } else { format!("{}:", prefix) };
+                vec![("start key", start), ("end key", end)]
+            }
+            AdminAction::SetOption => vec![("option", String::new()), ("value", String::new())],
+            AdminAction::DropCf => vec![("type the column family name to confirm", String::new())],
+            AdminAction::CreateCf => vec![("name", String::new()), ("options (name=value;...)", String::new())],
+            AdminAction::CopyCf => vec![("new column family", format!("{}_copy", menu.cf()))],
+            AdminAction::ShowOptions => {
+                let cf = menu.cf().to_string();
+                let text = match crate::admin::read_cf_options(&self.db_path, &cf) {
+                    Ok(options) => options.iter().map(|(name, value)| format!("{} = {}", name, value)).collect::<Vec<_>>().join("\n"),
+                    Err(e) => format!("Error reading options: {}", e),
+                };
+                self.open_text_popup("options", format!("options of {}:\n{}", cf, text));
+                return;
+            }
+            _ => Vec::new(),
+        };
+        menu.stage = if !fields.is_empty() {
+            AdminStage::Input { action, fields, field: 0 }
+        } else {
+            AdminOp::from_fields(menu.cf(), action, &[]).map_or(AdminStage::Menu, AdminStage::Confirm)
+        };
+    }
+
+    pub fn confirm_admin_op(&mut self) {
+        let Some(AdminStage::Confirm(op)) = self.admin_menu.take().map(|m| m.stage) else { return };
+        if let Some(task) = &self.admin_task {
+            self.status_message = Some(format!("still busy: {}", task.description));
+            return;
+        }
+        self.admin_task = Some(AdminTask::spawn(op, &self.db_path, &self.overrides));
+    }
+
+    /// Grows or shrinks the detail panel by `delta` percent of the width.
+    pub fn resize_detail_panel(&mut self, delta: i16) {
+        self.detail_width = self.detail_width.saturating_add_signed(delta).clamp(20, 80);
+    }
+
+    pub fn scroll_detail_panel(&mut self, delta: i32) {
+        self.detail_scroll = (self.detail_scroll as i32 + delta).max(0) as u16;
+    }
+
+    pub fn open_text_popup(&mut self, title: &'static str, text: String) {
+        self.show_raw_data = Some(text);
+        self.popup_title = title;
+        self.raw_scroll = 0;
+        self.raw_scroll_x = 0;
+    }
+
+    pub fn scroll_text_popup(&mut self, delta: i32) {
+        self.raw_scroll = (self.raw_scroll as i32 + delta).max(0) as u16;
+    }
+
+    pub fn copy_text(&mut self, label: &str, text: &str) {
+        self.status_message = Some(match crate::clipboard::copy_to_clipboard(text) {
+            Ok(_) => format!("copied {} ({} bytes)", label, text.len()),
+            Err(e) => format!("copy failed: {}", e),
+        });
+    }
+
+    /// Selects a row of the current table, clamped to its last row, and shows its page.
+    pub fn select_row(&mut self, row: usize) {
+        let Some(table) = &self.selected_table else { return };
+        let count = self.get_filtered_records(table).len();
+        if count == 0 {
+            return;
+        }
+        let row = row.min(count - 1);
+        self.selected_row = Some(row);
+        self.current_page = row / self.rows_per_page.max(1);
+        self.scroll_y = (self.current_page * self.rows_per_page.max(1)) as u16;
+    }
+
+    pub fn selected_record(&self) -> Option<Record> {
+        let table = self.selected_table.as_ref()?;
+        let row = self.selected_row?;
+        self.get_filtered_records(table).into_iter().nth(row)
+    }
+
+    /// Marks or unmarks the selected row; marking a third row drops the oldest mark.
+    pub fn toggle_mark(&mut self) {
+        let Some(record) = self.selected_record() else { return };
+        if let Some(pos) = self.marked.iter().position(|k| *k == record.key) {
+            self.marked.remove(pos);
+            return;
+        }
+        self.marked.push(record.key);
+        if self.marked.len() > 2 {
+            self.marked.remove(0);
+        }
+        self.marked_page = self.selected_table.clone().map(|table| (table, self.current_page));
+    }
+
+    /// Drops the marks once another table or page is shown.
+    pub fn drop_stale_marks(&mut self) {
+        let page = self.selected_table.clone().map(|table| (table, self.current_page));
+        if !self.marked.is_empty() && page != self.marked_page {
+            self.marked.clear();
+        }
+    }
+
+    /// Compares the two marked rows, or the marked row with the selected one.
+    pub fn open_record_diff(&mut self) {
+        let Some(table) = &self.selected_table else { return };
+        let mut keys = self.marked.clone();
+        if keys.len() == 1 && let Some(record) = self.selected_record() && record.key != keys[0] {
+            keys.push(record.key);
+        }
+        if keys.len() != 2 {
+            self.status_message = Some("mark two rows with m to compare them".to_string());
+            return;
+        }
+        let Some(records) = self.data_manager.get_records().get(table) else { return };
+        let find = |key: &str| records.iter().find(|r| r.key == key).cloned();
+        match (find(&keys[0]), find(&keys[1])) {
+            (Some(left), Some(right)) => self.record_diff = Some(RecordDiff::new(left, right)),
+            _ => self.status_message = Some("a marked row is no longer in the table".to_string()),
+        }
+    }
+
+    pub fn copy_selected(&mut self, target: CopyTarget) {
+        if let Some(record) = self.selected_record() {
+            self.copy_text(target.label(), &target.render(&record));
+        }
+    }
+
+    pub fn open_hex_view(&mut self, record: &Record) {
+        self.hex_view = Some(HexView::new(&record.key, &record.raw_data));
+    }
+
     pub fn visible_page_indices(&self, total_pages: usize) -> Vec<usize> {
         if total_pages == 0 { return vec![]; }
         let last = total_pages.saturating_sub(1);
@@ -74,27 +634,24 @@ impl App {
         }
         set.into_iter().collect()
     }
-    pub fn calculate_column_widths(&self, record_type: &str, max_width: u16) -> Vec<u16> {
-        let headers = match self.data_manager.get_headers().get(record_type) {
-            Some(h) => h,
-            None => return vec![max_width],
-        };
+    /// Width of every displayed column: its natural width capped at 50 characters, or the
+    /// width fixed in the layout, which is used as is. Empty for an unknown record type.
+    pub fn calculate_column_widths(&self, record_type: &str) -> Vec<u16> {
+        let headers = &self.display_headers(record_type);
+        let records = self.data_manager.get_records().get(record_type).map(Vec::as_slice).unwrap_or_default();
 
-        let records = match self.data_manager.get_records().get(record_type) {
-            Some(r) => r,
-            None => return vec![max_width],
-        };
-
-        let mut column_widths: Vec<usize> = headers.iter().enumerate().map(|(i, h)| {
+        let formats = self.time_formats(record_type, headers);
+        let cells = self.row_cells(record_type, headers, &formats);
+        let mut column_widths: Vec<usize> = headers.iter().map(|h| {
             let base_len = h.len() + 1;
-            if self.sort_column == Some(i) { base_len + 3 } else { base_len }
+            if self.sort_position(h).is_some() { base_len + 4 } else { base_len }
         }).collect();
 
         for record in records {
-            let row_data = record.to_table_row(headers);
+            let row_data = cells.get(record);
             for (i, cell) in row_data.iter().enumerate() {
                 if i < column_widths.len() {
-                    let cell_width = if self.sort_column == Some(i) {
+                    let cell_width = if self.sort_position(&headers[i]).is_some() {
                         cell.len() + 2
                     } else {
                         cell.len()
@@ -104,24 +661,150 @@ impl App {
             }
         }
 
-        let total_width: usize = column_widths.iter().sum();
-        let available_width = max_width as usize;
+        let layout = self.layouts.get(record_type);
+        column_widths.iter().zip(headers).map(|(&width, header)| {
+            layout.and_then(|l| l.columns.iter().find(|c| &c.name == header))
+                .and_then(|c| c.width)
+                .unwrap_or(width as u16)
+        }).collect()
+    }
+
+    /// Headers shown for `record_type`: the key followed by the visible fields in layout order.
+    pub fn display_headers(&self, record_type: &str) -> Vec<String> {
+        let Some(headers) = self.data_manager.get_headers().get(record_type) else { return vec![] };
+        let discovered = &headers[1..];
+        let mut display = vec![headers[0].clone()];
+        match self.layouts.get(record_type) {
+            Some(layout) => display.extend(layout.resolve(discovered).into_iter().filter(|c| c.visible).map(|c| c.name)),
+            None => display.extend(discovered.iter().cloned()),
+        }
+        display
+    }
+
+    /// The time format configured for `column`; the TTL write time is always epoch seconds.
+    pub fn time_format(&self, record_type: &str, column: &str) -> TimeFormat {
+        if column == crate::models::WRITTEN_COLUMN {
+            return TimeFormat::Seconds;
+        }
+        self.layouts.get(record_type)
+            .and_then(|l| l.columns.iter().find(|c| c.name == column))
+            .map(|c| c.time)
+            .unwrap_or_default()
+    }
+
+    pub fn time_formats(&self, record_type: &str, headers: &[String]) -> Vec<TimeFormat> {
+        headers.iter().map(|h| self.time_format(record_type, h)).collect()
+    }
+
+    /// Rebuilds the row cache when the selected table, its data, columns, time formats or zone changed.
+    pub fn refresh_row_cache(&mut self) {
+        let Some(record_type) = self.selected_table.clone() else { return };
+        let headers = self.display_headers(&record_type);
+        let formats = self.time_formats(&record_type, &headers);
+        let generation = self.data_manager.generation;
+        let zone = self.zone;
+        let cache = &self.row_cache;
+        if cache.record_type == record_type && cache.generation == generation && cache.headers == headers && cache.formats == formats && cache.zone == zone {
+            return;
+        }
+        let rows = self.data_manager.get_records().get(&record_type)
+            .map(|records| records.iter().map(|r| (r.key.clone(), r.to_table_row(&headers, &formats, zone))).collect())
+            .unwrap_or_default();
+        self.row_cache = RowCache { record_type, generation, headers, formats, zone, rows };
+    }
+
+    pub fn row_cells<'a>(&'a self, record_type: &str, headers: &'a [String], formats: &'a [TimeFormat]) -> RowCells<'a> {
+        let cache = &self.row_cache;
+        let current = cache.record_type == record_type
+            && cache.generation == self.data_manager.generation
+            && cache.headers == headers
+            && cache.formats == formats
+            && cache.zone == self.zone;
+        RowCells { cached: current.then_some(&cache.rows), headers, formats, zone: self.zone }
+    }
+
+    /// Position of `column` among the sort keys, if it is sorted on.
+    pub fn sort_position(&self, column: &str) -> Option<usize> {
+        self.sort_keys.iter().position(|k| k.column == column)
+    }
+
+    /// Sorts by `column` after a header click. A plain click makes it the only sort key,
+    /// `add` appends it to the existing keys; clicking a sorted column flips its direction.
+    pub fn toggle_sort(&mut self, column: &str, add: bool) {
+        match self.sort_position(column) {
+            Some(pos) if add || self.sort_keys.len() == 1 => {
+                self.sort_keys[pos].ascending = !self.sort_keys[pos].ascending;
+            }
+            _ => {
+                if !add {
+                    self.sort_keys.clear();
+                }
+                self.sort_keys.push(SortKey { column: column.to_string(), ascending: true });
+            }
+        }
+    }
+
+    pub fn open_column_chooser(&mut self) {
+        let Some(record_type) = self.selected_table.clone() else { return };
+        let Some(headers) = self.data_manager.get_headers().get(&record_type) else { return };
+        let columns = self.layouts.get(&record_type).cloned().unwrap_or_default().resolve(&headers[1..]);
+        self.column_chooser = Some(ColumnChooser::new(&record_type, columns));
+    }
 
-        if total_width < available_width {
-            return column_widths.iter()
-                .map(|&width| width as u16)
-                .collect();
+    /// Saves the chooser's layout for its record type and closes it.
+    pub fn apply_column_chooser(&mut self) {
+        let Some(chooser) = self.column_chooser.take() else { return };
+        let layout = self.layouts.get(&chooser.record_type).cloned().unwrap_or_default().updated(chooser.columns);
+        if let Err(e) = self.layouts.set(&chooser.record_type, layout) {
+            self.status_message = Some(format!("layout not saved: {}", e));
         }
+        let visible = self.display_headers(&chooser.record_type);
+        self.sort_keys.retain(|k| visible.contains(&k.column));
+        self.col_offset = 0;
+    }
+
+    /// Picks the columns that fit into `max_width`: the key column is always shown on the
+    /// left, followed by as many of the remaining columns as fit starting at `col_offset`.
+    pub fn layout_columns(&self, record_type: &str, max_width: u16) -> ColumnLayout {
+        let natural = self.calculate_column_widths(record_type);
+        let available = max_width as usize;
+        let key_width = (natural.first().copied().unwrap_or(max_width) as usize).min(available / 2).max(1);
 
-        let mut widths = Vec::new();
-        for (i, &width) in column_widths.iter().enumerate() {
-            let ratio = width as f32 / total_width as f32;
-            let min_width = if i == 0 { 24 } else { 14 };
-            let allocated = ((available_width as f32 * ratio) as u16).max(min_width);
-            widths.push(allocated);
+        let mut layout = ColumnLayout { columns: vec![0], widths: vec![key_width as u16], hidden_left: 0, hidden_right: 0 };
+        let first = 1 + self.col_offset.min(natural.len().saturating_sub(2));
+        let mut used = key_width;
+        let mut next = first;
+        while next < natural.len() {
+            let needed = COLUMN_SPACING as usize + natural[next] as usize;
+            if used + needed > available {
+                // Always show at least one scrolled column, truncated if necessary
+                let rest = available.saturating_sub(used + COLUMN_SPACING as usize);
+                if layout.columns.len() == 1 && rest > 0 {
+                    layout.columns.push(next);
+                    layout.widths.push(rest as u16);
+                    next += 1;
+                }
+                break;
+            }
+            layout.columns.push(next);
+            layout.widths.push(natural[next]);
+            used += needed;
+            next += 1;
         }
+        layout.hidden_left = first.min(natural.len()) - 1;
+        layout.hidden_right = natural.len().saturating_sub(next);
+        layout
+    }
 
-        widths
+    pub fn scroll_columns(&mut self, delta: isize) {
+        let Some(table) = self.selected_table.clone() else { return };
+        if delta > 0 {
+            if self.layout_columns(&table, self.table_width).hidden_right > 0 {
+                self.col_offset += 1;
+            }
+        } else {
+            self.col_offset = self.col_offset.saturating_sub(delta.unsigned_abs());
+        }
     }
 
     pub fn get_total_pages(&self, record_type: &str, height: u16) -> usize {
@@ -136,30 +819,40 @@ impl App {
 
     pub fn get_filtered_records(&self, record_type: &str) -> Vec<Record> {
         let mut records = self.data_manager.get_records().get(record_type).unwrap().clone();
-        if !self.input.is_empty() {
-            records.retain(|r| r.key.contains(&self.input));
-        }
-        if let Some(sort_col) = self.sort_column {
-            records.sort_by(|a, b| {
-                let headers = self.data_manager.get_headers().get(record_type).unwrap();
-                let a_row = a.to_table_row(headers);
-                let b_row = b.to_table_row(headers);
-                let a_val = a_row.get(sort_col).map(|s| s.as_str()).unwrap_or("");
-                let b_val = b_row.get(sort_col).map(|s| s.as_str()).unwrap_or("");
-                
-                match (a_val.parse::<f64>(), b_val.parse::<f64>()) {
-                    (Ok(a_num), Ok(b_num)) => if self.sort_ascending {
-                        a_num.partial_cmp(&b_num).unwrap_or(std::cmp::Ordering::Equal)
-                    } else {
-                        b_num.partial_cmp(&a_num).unwrap_or(std::cmp::Ordering::Equal)
-                    },
-                    _ => if self.sort_ascending {
-                        a_val.cmp(b_val)
-                    } else {
-                        b_val.cmp(a_val)
+        let (conditions, key_filter) = crate::timestamps::parse_filter(&self.input, self.zone);
+        if !key_filter.is_empty() {
+            records.retain(|r| r.key.contains(&key_filter));
+        }
+        for condition in &conditions {
+            let format = self.time_format(record_type, &condition.column);
+            records.retain(|r| {
+                r.field(&condition.column)
+                    .and_then(|value| crate::timestamps::as_epoch_seconds(&condition.column, &value, format))
+                    .is_some_and(|seconds| condition.matches(seconds))
+            });
+        }
+        if !self.sort_keys.is_empty() {
+            let formats: Vec<TimeFormat> = self.sort_keys.iter().map(|k| self.time_format(record_type, &k.column)).collect();
+            let mut keyed: Vec<(Vec<SortValue>, Record)> = records.into_iter().map(|r| {
+                let values = self.sort_keys.iter().zip(&formats).map(|(k, &format)| SortValue::from_value(&k.column, r.field(&k.column).as_ref(), format)).collect();
+                (values, r)
+            }).collect();
+            keyed.sort_by(|(a, _), (b, _)| {
+                for (i, sort_key) in self.sort_keys.iter().enumerate() {
+                    let ordering = match (a[i].is_null(), b[i].is_null()) {
+                        (true, true) => std::cmp::Ordering::Equal,
+                        (true, false) => std::cmp::Ordering::Greater,
+                        (false, true) => std::cmp::Ordering::Less,
+                        (false, false) if sort_key.ascending => a[i].compare(&b[i]),
+                        (false, false) => b[i].compare(&a[i]),
+                    };
+                    if ordering != std::cmp::Ordering::Equal {
+                        return ordering;
                     }
                 }
+                std::cmp::Ordering::Equal
             });
+            records = keyed.into_iter().map(|(_, r)| r).collect();
         }
         records
     }
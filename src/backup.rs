@@ -0,0 +1,114 @@
+use rocksdb::backup::{BackupEngine, BackupEngineInfo, BackupEngineOptions, RestoreOptions};
+use rocksdb::checkpoint::Checkpoint;
+use crate::options::{OpenMode, Overrides};
+use rocksdb::{Env, DB};
+use std::path::Path;
+
+/// Backups, and checkpoints a read-only handle cannot take, need a writable handle, which is
+/// only opened when the database was opened writable.
+fn open(db_path: &str, overrides: &Overrides) -> Result<DB, String> {
+    if overrides.mode != OpenMode::Writable {
+        return Err("this needs the database opened with --writable".to_string());
+    }
+    crate::data::open_writable(db_path, overrides).map_err(|e| format!("cannot open {} for writing: {}", db_path, e))
+}
+
+fn engine(backup_dir: &str) -> Result<BackupEngine, String> {
+    let opts = BackupEngineOptions::new(backup_dir).map_err(|e| e.to_string())?;
+    let env = Env::new().map_err(|e| e.to_string())?;
+    BackupEngine::open(&opts, &env).map_err(|e| e.to_string())
+}
+
+/// A sibling of the database directory with a timestamp suffix, e.g. `mydb.checkpoint-20240131-101500`.
+pub fn default_checkpoint_dir(db_path: &str) -> String {
+    format!("{}.checkpoint-{}", db_path.trim_end_matches('/'), chrono::Local::now().format("%Y%m%d-%H%M%S"))
+}
+
+pub fn default_backup_dir(db_path: &str) -> String {
+    format!("{}.backups", db_path.trim_end_matches('/'))
+}
+
+/// Creates a `Checkpoint` (hard-linked where possible) in `target`, which must not exist yet.
+/// It is taken from the read-only or secondary handle the viewer reads with; a writable handle
+/// is only tried when that fails and the database was opened writable.
+pub fn create_checkpoint(db_path: &str, target: &str, overrides: &Overrides) -> Result<(), String> {
+    if Path::new(target).exists() {
+        return Err(format!("{} already exists", target));
+    }
+    let checkpoint = |db: &DB| Checkpoint::new(db).and_then(|c| c.create_checkpoint(target)).map_err(|e| e.to_string());
+    let read_only = crate::data::open_read_only(db_path, overrides).and_then(|(db, _)| checkpoint(&db));
+    match read_only {
+        Err(e) if overrides.mode != OpenMode::Writable => Err(format!("{} (a writable handle needs --writable)", e)),
+        Err(_) => checkpoint(&open(db_path, overrides)?),
+        Ok(()) => Ok(()),
+    }
+}
+
+/// Flushes the memtables and adds a backup to `backup_dir`; returns the new backup's id.
+pub fn create_backup(db_path: &str, backup_dir: &str, overrides: &Overrides) -> Result<u32, String> {
+    let db = open(db_path, overrides)?;
+    let mut engine = engine(backup_dir)?;
+    engine.create_new_backup_flush(&db, true).map_err(|e| e.to_string())?;
+    Ok(engine.get_backup_info().iter().map(|b| b.backup_id).max().unwrap_or_default())
+}
+
+pub fn list_backups(backup_dir: &str) -> Result<Vec<BackupEngineInfo>, String> {
+    if !Path::new(backup_dir).exists() {
+        return Ok(Vec::new());
+    }
+    Ok(engine(backup_dir)?.get_backup_info())
+}
+
+/// Checks that every file of the backup exists with the expected size.
+pub fn verify_backup(backup_dir: &str, backup_id: u32) -> Result<(), String> {
+    engine(backup_dir)?.verify_backup(backup_id).map_err(|e| e.to_string())
+}
+
+/// Restores a backup into `target`, which must not exist yet so a live database is never overwritten.
+pub fn restore_backup(backup_dir: &str, backup_id: u32, target: &str) -> Result<(), String> {
+    if Path::new(target).exists() {
+        return Err(format!("{} already exists", target));
+    }
+    engine(backup_dir)?
+        .restore_from_backup(target, target, &RestoreOptions::default(), backup_id)
+        .map_err(|e| e.to_string())
+}
+
+pub fn format_timestamp(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_else(|| timestamp.to_string())
+}
+
+/// What the path prompt of the backups screen will do once a directory is entered.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PathAction {
+    Checkpoint,
+    Restore(u32),
+    BackupDir,
+}
+
+/// The backups screen: the backups in one backup directory and an optional path prompt.
+pub struct BackupsState {
+    pub backup_dir: String,
+    pub backups: Result<Vec<BackupEngineInfo>, String>,
+    pub index: usize,
+    pub prompt: Option<(PathAction, String)>,
+}
+
+impl BackupsState {
+    pub fn new(backup_dir: String) -> Self {
+        let backups = list_backups(&backup_dir);
+        Self { backup_dir, backups, index: 0, prompt: None }
+    }
+
+    pub fn reload(&mut self) {
+        self.backups = list_backups(&self.backup_dir);
+        let count = self.backups.as_ref().map_or(0, |b| b.len());
+        self.index = self.index.min(count.saturating_sub(1));
+    }
+
+    pub fn selected_id(&self) -> Option<u32> {
+        self.backups.as_ref().ok()?.get(self.index).map(|b| b.backup_id)
+    }
+}
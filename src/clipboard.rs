@@ -0,0 +1,81 @@
+use crate::models::Record;
+use std::io::{self, Write};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CopyTarget {
+    Key,
+    Value,
+    RawHex,
+    RawBase64,
+    Row,
+}
+
+impl CopyTarget {
+    pub const ALL: [CopyTarget; 5] = [CopyTarget::Key, CopyTarget::Value, CopyTarget::RawHex, CopyTarget::RawBase64, CopyTarget::Row];
+
+    pub fn shortcut(self) -> char {
+        match self {
+            CopyTarget::Key => 'k',
+            CopyTarget::Value => 'v',
+            CopyTarget::RawHex => 'h',
+            CopyTarget::RawBase64 => 'b',
+            CopyTarget::Row => 'r',
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CopyTarget::Key => "key",
+            CopyTarget::Value => "value (JSON)",
+            CopyTarget::RawHex => "raw value (hex)",
+            CopyTarget::RawBase64 => "raw value (base64)",
+            CopyTarget::Row => "row (JSON)",
+        }
+    }
+
+    pub fn from_shortcut(c: char) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.shortcut() == c)
+    }
+
+    pub fn render(self, record: &Record) -> String {
+        match self {
+            CopyTarget::Key => record.key.clone(),
+            CopyTarget::Value => record.pretty_value(),
+            CopyTarget::RawHex => record.raw_data.iter().map(|b| format!("{:02x}", b)).collect(),
+            CopyTarget::RawBase64 => base64_encode(&record.raw_data),
+            CopyTarget::Row => serde_json::to_string_pretty(&record.row_json()).unwrap_or_default(),
+        }
+    }
+}
+
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub fn base64_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = ((b[0] as u32) << 16) | ((b[1] as u32) << 8) | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_ALPHABET[((n >> (18 - 6 * i)) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Puts `text` on the system clipboard with the OSC 52 escape, which the terminal handles
+/// itself, so it also works over SSH. Inside tmux the sequence is wrapped in a DCS passthrough.
+pub fn copy_to_clipboard(text: &str) -> io::Result<()> {
+    let osc = format!("\x1b]52;c;{}\x07", base64_encode(text.as_bytes()));
+    let sequence = if std::env::var_os("TMUX").is_some() {
+        format!("\x1bPtmux;{}\x1b\\", osc.replace('\x1b', "\x1b\x1b"))
+    } else {
+        osc
+    };
+    let mut stdout = io::stdout();
+    stdout.write_all(sequence.as_bytes())?;
+    stdout.flush()
+}
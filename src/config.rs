@@ -0,0 +1,303 @@
+use crate::keymap::{Action, Keymap, PRESETS};
+use crate::layout::TableLayout;
+use crate::models::{Decoder, KeySchema, RecordRules};
+use crate::options::{OpenMode, Overrides};
+use ratatui::style::Color;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+pub const PROJECT_FILE: &str = ".rocksdb-viewer.toml";
+
+/// `$XDG_CONFIG_HOME/rocksdb-viewer`, falling back to `~/.config/rocksdb-viewer`.
+pub fn config_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("rocksdb-viewer"))
+}
+
+/// The nearest `.rocksdb-viewer.toml` in the working directory or one of its parents.
+fn project_file() -> Option<PathBuf> {
+    let cwd = std::env::current_dir().ok()?;
+    cwd.ancestors().map(|dir| dir.join(PROJECT_FILE)).find(|path| path.is_file())
+}
+
+/// Startup settings that can also be given on the command line, where the flag wins.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Settings {
+    pub db_path: Option<String>,
+    pub tz: Option<String>,
+    pub comparator: Option<String>,
+    pub merge_operator: Option<String>,
+    pub ttl: Option<i64>,
+    pub mode: Option<OpenMode>,
+}
+
+impl Settings {
+    fn from_table(table: &Table, section: &str) -> Result<Self, String> {
+        let mut settings = Settings::default();
+        for (name, value) in table {
+            let text = || value.as_str().map(str::to_string).ok_or_else(|| format!("[{}] {} must be a string", section, name));
+            match name.as_str() {
+                "db_path" => settings.db_path = Some(text()?),
+                "tz" => settings.tz = Some(text()?),
+                "comparator" => settings.comparator = Some(text()?),
+                "merge_operator" => settings.merge_operator = Some(text()?),
+                "ttl" => settings.ttl = Some(value.as_integer().ok_or_else(|| format!("[{}] ttl must be a number of seconds", section))?),
+                "mode" => settings.mode = Some(OpenMode::parse(&text()?).map_err(|e| format!("[{}] {}", section, e))?),
+                "writable" => return Err(format!("[{}] writable has been folded into mode; use mode = \"writable\"", section)),
+                other => return Err(format!("unknown setting {} in [{}]", other, section)),
+            }
+        }
+        Ok(settings)
+    }
+
+    /// Fills the settings missing here from `fallback`.
+    pub fn or(self, fallback: &Settings) -> Settings {
+        Settings {
+            db_path: self.db_path.or_else(|| fallback.db_path.clone()),
+            tz: self.tz.or_else(|| fallback.tz.clone()),
+            comparator: self.comparator.or_else(|| fallback.comparator.clone()),
+            merge_operator: self.merge_operator.or_else(|| fallback.merge_operator.clone()),
+            ttl: self.ttl.or(fallback.ttl),
+            mode: self.mode.or(fallback.mode),
+        }
+    }
+
+    /// The overrides these settings force on the database they open.
+    pub fn overrides(&self) -> Overrides {
+        Overrides {
+            comparator: self.comparator.clone(),
+            merge_operator: self.merge_operator.clone(),
+            ttl: self.ttl,
+            mode: self.mode.unwrap_or_default(),
+        }
+    }
+}
+
+/// A `[profiles.NAME]` section: settings plus decoders and key schemas for its record types,
+/// which replace the top-level ones of the same record type.
+#[derive(Clone, Debug, Default)]
+pub struct Profile {
+    pub settings: Settings,
+    pub record_rules: BTreeMap<String, RecordRules>,
+}
+
+/// Colors of the main screen.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    pub accent: Color,
+    pub footer_bg: Color,
+    pub footer_fg: Color,
+    pub search: Color,
+    pub header: Color,
+    pub selected: Color,
+    pub marked: Color,
+    pub expired: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            accent: Color::Blue,
+            footer_bg: Color::Blue,
+            footer_fg: Color::Green,
+            search: Color::Magenta,
+            header: Color::Yellow,
+            selected: Color::Blue,
+            marked: Color::Magenta,
+            expired: Color::Red,
+        }
+    }
+}
+
+impl Theme {
+    fn from_table(table: &Table) -> Result<Self, String> {
+        let mut theme = Theme::default();
+        for (name, value) in table {
+            let color = value.as_str()
+                .and_then(|s| s.parse::<Color>().ok())
+                .ok_or_else(|| format!("[theme] {} must be a color name or #rrggbb", name))?;
+            match name.as_str() {
+                "accent" => theme.accent = color,
+                "footer_bg" => theme.footer_bg = color,
+                "footer_fg" => theme.footer_fg = color,
+                "search" => theme.search = color,
+                "header" => theme.header = color,
+                "selected" => theme.selected = color,
+                "marked" => theme.marked = color,
+                "expired" => theme.expired = color,
+                other => return Err(format!("unknown theme color {}", other)),
+            }
+        }
+        Ok(theme)
+    }
+}
+
+/// The merged configuration of the user file and the project file.
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+    pub defaults: Settings,
+    pub profiles: BTreeMap<String, Profile>,
+    pub record_rules: BTreeMap<String, RecordRules>,
+    pub layouts: HashMap<String, TableLayout>,
+    pub theme: Theme,
+    /// The key preset plus the `[keys]` bindings.
+    pub keys: Keymap,
+}
+
+impl Config {
+    /// Reads `config.toml` from the config directory and then the project file, whose
+    /// entries replace those of the user file.
+    pub fn load() -> Result<Self, String> {
+        let mut merged = Table::new();
+        let mut last_source = None;
+        for path in [config_dir().map(|dir| dir.join("config.toml")), project_file()].into_iter().flatten() {
+            if !path.is_file() {
+                continue;
+            }
+            let text = std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            let mut table: Table = text.parse().map_err(|e| format!("{}: {}", path.display(), e))?;
+            resolve_db_paths(&mut table, path.parent().unwrap_or(Path::new(".")));
+            merge(&mut merged, table);
+            last_source = Some(path);
+        }
+        Self::from_table(&merged).map_err(|e| match last_source {
+            Some(path) => format!("{} (config read from {})", e, path.display()),
+            None => e,
+        })
+    }
+
+    fn from_table(table: &Table) -> Result<Self, String> {
+        let mut config = Config::default();
+        let section = |name: &str| -> Result<Option<&Table>, String> {
+            match table.get(name) {
+                None => Ok(None),
+                Some(value) => value.as_table().map(Some).ok_or_else(|| format!("[{}] must be a table", name)),
+            }
+        };
+        for name in table.keys() {
+            if !["defaults", "profiles", "record_types", "theme", "keys"].contains(&name.as_str()) {
+                return Err(format!("unknown section [{}]", name));
+            }
+        }
+        if let Some(defaults) = section("defaults")? {
+            config.defaults = Settings::from_table(defaults, "defaults")?;
+        }
+        for (name, profile) in section("profiles")?.into_iter().flatten() {
+            let label = format!("profiles.{}", name);
+            let mut profile = profile.as_table().ok_or_else(|| format!("[{}] must be a table", label))?.clone();
+            let record_types = profile.remove("record_types");
+            let mut parsed = Profile { settings: Settings::from_table(&profile, &label)?, record_rules: BTreeMap::new() };
+            for (record_type, rules) in record_types.as_ref().and_then(Value::as_table).into_iter().flatten() {
+                let label = format!("{}.record_types.{}", label, record_type);
+                let (rules, layout) = parse_record_type(rules, &label)?;
+                if layout.is_some() {
+                    return Err(format!("[{}] columns belong in the top-level [record_types.{}]", label, record_type));
+                }
+                parsed.record_rules.insert(record_type.clone(), rules);
+            }
+            config.profiles.insert(name.clone(), parsed);
+        }
+        for (record_type, rules) in section("record_types")?.into_iter().flatten() {
+            let (rules, layout) = parse_record_type(rules, &format!("record_types.{}", record_type))?;
+            config.record_rules.insert(record_type.clone(), rules);
+            if let Some(layout) = layout {
+                config.layouts.insert(record_type.clone(), layout);
+            }
+        }
+        if let Some(theme) = section("theme")? {
+            config.theme = Theme::from_table(theme)?;
+        }
+        if let Some(keys) = section("keys")? {
+            config.keys = parse_keys(keys)?;
+        }
+        Ok(config)
+    }
+
+    /// The record rules of a profile: its own record types over the top-level ones.
+    pub fn record_rules(&self, profile: Option<&Profile>) -> BTreeMap<String, RecordRules> {
+        let mut rules = self.record_rules.clone();
+        if let Some(profile) = profile {
+            rules.extend(profile.record_rules.clone());
+        }
+        rules
+    }
+}
+
+/// Reads `[keys]`: a `preset`, then `action = "key"` or `action = ["key", ...]` entries, and
+/// `"key" = "action"` entries, which may also name another key or `none`.
+fn parse_keys(table: &Table) -> Result<Keymap, String> {
+    let preset = match table.get("preset") {
+        None => "default",
+        Some(value) => value.as_str().ok_or("[keys] preset must be a string")?,
+    };
+    let mut keymap = Keymap::preset(preset).ok_or_else(|| format!("[keys] unknown preset {}, expected one of {}", preset, PRESETS.join(", ")))?;
+    for (name, value) in table.iter().filter(|(name, _)| *name != "preset") {
+        if Action::from_name(name).is_some() {
+            let keys = match value {
+                Value::String(key) => vec![key.as_str()],
+                Value::Array(keys) => keys.iter().map(|key| key.as_str().ok_or_else(|| format!("[keys] {} must list keys as strings", name))).collect::<Result<_, _>>()?,
+                _ => return Err(format!("[keys] {} must be a key or a list of keys", name)),
+            };
+            for key in keys {
+                keymap.bind(key, name).map_err(|e| format!("[keys] {}", e))?;
+            }
+        } else {
+            let target = value.as_str().ok_or_else(|| format!("[keys] {} must map to an action such as \"down\"", name))?;
+            keymap.bind(name, target).map_err(|e| format!("[keys] {}", e))?;
+        }
+    }
+    Ok(keymap)
+}
+
+/// Reads one `[record_types.NAME]` table: its decoder and key schema, and its column layout if it has one.
+fn parse_record_type(value: &Value, label: &str) -> Result<(RecordRules, Option<TableLayout>), String> {
+    let table = value.as_table().ok_or_else(|| format!("[{}] must be a table", label))?;
+    let mut rules = RecordRules::default();
+    let mut layout = None;
+    for (name, value) in table {
+        match (name.as_str(), value) {
+            ("decoder", Value::String(decoder)) => {
+                rules.decoder = Decoder::from_name(decoder)
+                    .ok_or_else(|| format!("[{}] unknown decoder {}; expected one of {}", label, decoder, Decoder::NAMES.join(", ")))?;
+            }
+            ("key_schema", Value::String(pattern)) => rules.key_schema = Some(KeySchema::parse(pattern).map_err(|e| format!("[{}] {}", label, e))?),
+            ("columns", Value::Array(columns)) => {
+                let columns = serde_json::to_value(columns).map_err(|e| format!("[{}] columns: {}", label, e))?;
+                layout = Some(TableLayout::from_json(&serde_json::json!({ "columns": columns })));
+            }
+            (other, _) => return Err(format!("[{}] unknown or mistyped setting {}", label, other)),
+        }
+    }
+    Ok((rules, layout))
+}
+
+/// Makes relative database paths relative to the file they are written in.
+fn resolve_db_paths(table: &mut Table, dir: &Path) {
+    let resolve = |settings: Option<&mut Value>| {
+        if let Some(Value::String(path)) = settings.and_then(|s| s.get_mut("db_path")) && Path::new(path.as_str()).is_relative() {
+            *path = dir.join(&*path).to_string_lossy().to_string();
+        }
+    };
+    resolve(table.get_mut("defaults"));
+    if let Some(Value::Table(profiles)) = table.get_mut("profiles") {
+        for (_, profile) in profiles.iter_mut() {
+            resolve(Some(profile));
+        }
+    }
+}
+
+/// Merges `overlay` into `base`; tables merge key by key, everything else is replaced.
+fn merge(base: &mut Table, overlay: Table) {
+    for (name, value) in overlay {
+        match (base.get_mut(&name), value) {
+            (Some(Value::Table(base_table)), Value::Table(overlay_table)) => merge(base_table, overlay_table),
+            (_, value) => {
+                base.insert(name, value);
+            }
+        }
+    }
+}
@@ -1,37 +1,175 @@
-use crate::models::{Record, deserialize_record};
-use rocksdb::{DB, IteratorMode, Options};
+use crate::analytics::TypeStats;
+use crate::models::{Record, RuleSet, deserialize_record, deserialize_ttl_record, EXPIRES_COLUMN, WRITTEN_COLUMN};
+use crate::options::{OpenMode, Overrides, Ttl};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use rocksdb::{DB, IteratorMode, ReadOptions, Snapshot};
 use std::collections::HashMap;
-use std::sync::mpsc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc};
 use std::thread;
 use std::time::{Duration, SystemTime};
 
+/// One pass over the database: the records grouped by type plus their size statistics.
+#[derive(Default)]
+pub struct LoadedData {
+    pub records: HashMap<String, Vec<Record>>,
+    pub type_stats: HashMap<String, TypeStats>,
+    /// Sequence number of the snapshot the records were read from.
+    pub sequence: Option<u64>,
+    /// Set for TTL databases, whose values carry a write time suffix.
+    pub ttl: Option<Ttl>,
+    /// Why the database could not be opened, or the first error that ended the scan early.
+    pub error: Option<String>,
+}
+
+impl LoadedData {
+    fn push(&mut self, rule_set: &RuleSet, key_bytes: &[u8], value_bytes: &[u8]) {
+        let key = String::from_utf8_lossy(key_bytes).to_string();
+        let record = match self.ttl {
+            Some(ttl) => deserialize_ttl_record(rule_set, &key, value_bytes, ttl),
+            None => deserialize_record(rule_set, &key, value_bytes),
+        };
+        self.type_stats.entry(record.record_type.clone()).or_default().add(&key, key_bytes.len(), value_bytes.len());
+        self.records.entry(record.record_type.clone()).or_default().push(record);
+    }
+}
+
 pub trait DataLoader {
-    fn load_records(&self) -> HashMap<String, Vec<Record>>;
+    /// Opens the handle records are read from; it stays open while its snapshot is shown.
+    fn open(&self) -> Result<DB, String>;
+    /// Secondary instances cannot iterate a snapshot; they stay at the point they caught up to instead.
+    fn uses_snapshots(&self) -> bool;
+    fn load_records(&self, db: &DB, snapshot: Option<&Snapshot>) -> LoadedData;
+    fn load_stats(&self) -> Result<Vec<CfStats>, String>;
     fn has_changed(&self) -> bool;
 }
 
+/// RocksDB properties of one column family, as shown on the stats screen.
+#[derive(Clone, Debug, Default)]
+pub struct CfStats {
+    pub name: String,
+    pub estimate_num_keys: Option<u64>,
+    pub total_sst_files_size: Option<u64>,
+    pub live_sst_files_size: Option<u64>,
+    pub cur_size_all_mem_tables: Option<u64>,
+    pub size_all_mem_tables: Option<u64>,
+    pub block_cache_usage: Option<u64>,
+    pub levelstats: String,
+    pub stats: String,
+}
+
+/// Opens the database for reading: read-only, or as a secondary instance when its overrides ask for it.
+pub fn open_read_only(db_path: &str, overrides: &Overrides) -> Result<(DB, Vec<String>), String> {
+    let (mut opts, descriptors) = crate::options::open_options(db_path, overrides)?;
+    let cf_names = descriptors.iter().map(|d| d.name().to_string()).collect();
+    let db = if overrides.mode == OpenMode::Secondary {
+        // A secondary instance has to keep every file of the primary open
+        opts.set_max_open_files(-1);
+        DB::open_cf_descriptors_as_secondary(&opts, Path::new(db_path), secondary_dir(db_path).as_path(), descriptors)
+    } else {
+        DB::open_cf_descriptors_read_only(&opts, db_path, descriptors, false)
+    };
+    Ok((db.map_err(|e| e.to_string())?, cf_names))
+}
+
+/// Counts secondary opens so that instances open at the same time never share a directory.
+static SECONDARY_OPENS: AtomicU64 = AtomicU64::new(0);
+
+/// Where a secondary instance of `db_path` keeps its own logs; a fresh one for every open.
+fn secondary_dir(db_path: &str) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    db_path.hash(&mut hasher);
+    let open = SECONDARY_OPENS.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!("{}{:016x}-{}", secondary_dir_prefix(), hasher.finish(), open))
+}
+
+fn secondary_dir_prefix() -> String {
+    format!("rocksdb-viewer-secondary-{}-", std::process::id())
+}
+
+/// Removes the directories of this process's secondary instances.
+pub fn remove_secondary_dirs() {
+    let prefix = secondary_dir_prefix();
+    for entry in std::fs::read_dir(std::env::temp_dir()).into_iter().flatten().flatten() {
+        if entry.file_name().to_string_lossy().starts_with(&prefix) {
+            let _ = std::fs::remove_dir_all(entry.path());
+        }
+    }
+}
+
+/// Opens the database for writing with all of its column families; fails while another process holds the lock.
+pub fn open_writable(db_path: &str, overrides: &Overrides) -> Result<DB, String> {
+    let (opts, descriptors) = crate::options::open_options(db_path, overrides)?;
+    DB::open_cf_descriptors(&opts, db_path, descriptors).map_err(|e| e.to_string())
+}
+
+pub fn collect_stats(db_path: &str, overrides: &Overrides) -> Result<Vec<CfStats>, String> {
+    let (db, cf_names) = open_read_only(db_path, overrides).map_err(|e| e.to_string())?;
+    let mut stats = Vec::new();
+    for name in cf_names {
+        let Some(cf) = db.cf_handle(&name) else { continue };
+        let int = |property: &str| db.property_int_value_cf(cf, property).ok().flatten();
+        let text = |property: &str| db.property_value_cf(cf, property).ok().flatten().unwrap_or_default();
+        stats.push(CfStats {
+            estimate_num_keys: int("rocksdb.estimate-num-keys"),
+            total_sst_files_size: int("rocksdb.total-sst-files-size"),
+            live_sst_files_size: int("rocksdb.live-sst-files-size"),
+            cur_size_all_mem_tables: int("rocksdb.cur-size-all-mem-tables"),
+            size_all_mem_tables: int("rocksdb.size-all-mem-tables"),
+            block_cache_usage: int("rocksdb.block-cache-usage"),
+            levelstats: text("rocksdb.levelstats"),
+            stats: text("rocksdb.stats"),
+            name,
+        });
+    }
+    Ok(stats)
+}
+
+/// Reads every record of the default column family, as of `snapshot` when there is one, decoded
+/// with `rule_set`. The scan stops at the first error, which is kept with what was read before it.
+fn read_snapshot(db_path: &str, overrides: &Overrides, rule_set: &RuleSet, db: &DB, snapshot: Option<&Snapshot>) -> LoadedData {
+    let mut loaded = LoadedData { ttl: crate::options::detect_ttl(db_path, overrides), sequence: Some(db.latest_sequence_number()), ..Default::default() };
+    let mut read_opts = ReadOptions::default();
+    if let Some(snapshot) = snapshot {
+        read_opts.set_snapshot(snapshot);
+    }
+    for item in db.iterator_opt(IteratorMode::Start, read_opts) {
+        match item {
+            Ok((key_bytes, value_bytes)) => loaded.push(rule_set, &key_bytes, &value_bytes),
+            Err(e) => {
+                loaded.error = Some(e.to_string());
+                break;
+            }
+        }
+    }
+    loaded
+}
+
 #[derive(Clone)]
 pub struct FullDataLoader {
     db_path: String,
+    overrides: Overrides,
+    rule_set: RuleSet,
     last_load_time: SystemTime,
 }
 
 impl DataLoader for FullDataLoader {
-    fn load_records(&self) -> HashMap<String, Vec<Record>> {
-        let mut opts = Options::default();
-        opts.create_if_missing(false);
-        let mut records = HashMap::new();
-        if let Ok(db) = DB::open_for_read_only(&opts, &self.db_path, false) {
-            let iter = db.iterator(IteratorMode::Start);
-            for item in iter {
-                let (key_bytes, value_bytes) = item.unwrap();
-                let key = String::from_utf8_lossy(&key_bytes).to_string();
-                let value = value_bytes.to_vec();
-                let record = deserialize_record(&key, &value);
-                records.entry(record.record_type.clone()).or_insert_with(Vec::new).push(record);
-            }
-        }
-        records
+    fn open(&self) -> Result<DB, String> {
+        open_read_only(&self.db_path, &self.overrides).map(|(db, _)| db)
+    }
+
+    fn uses_snapshots(&self) -> bool {
+        self.overrides.mode != OpenMode::Secondary
+    }
+
+    fn load_records(&self, db: &DB, snapshot: Option<&Snapshot>) -> LoadedData {
+        read_snapshot(&self.db_path, &self.overrides, &self.rule_set, db, snapshot)
+    }
+
+    fn load_stats(&self) -> Result<Vec<CfStats>, String> {
+        collect_stats(&self.db_path, &self.overrides)
     }
 
     fn has_changed(&self) -> bool {
@@ -48,48 +186,126 @@ pub struct DataManager<T: DataLoader> {
     pub loader: T,
     pub records: HashMap<String, Vec<Record>>,
     pub headers: HashMap<String, Vec<String>>,
-    tx: mpsc::Sender<HashMap<String, Vec<Record>>>,
-    pub rx: mpsc::Receiver<HashMap<String, Vec<Record>>>,
+    pub type_stats: HashMap<String, TypeStats>,
+    pub stats: Option<Result<Vec<CfStats>, String>>,
+    /// Sequence number of the snapshot currently shown.
+    pub sequence: Option<u64>,
+    /// Why the last load came back empty or incomplete.
+    pub load_error: Option<String>,
+    /// Bumped whenever the records change, so caches built from them know to rebuild.
+    pub generation: u64,
+    tx: mpsc::Sender<LoadedData>,
+    pub rx: mpsc::Receiver<LoadedData>,
+    stats_tx: mpsc::Sender<Result<Vec<CfStats>, String>>,
+    stats_rx: mpsc::Receiver<Result<Vec<CfStats>, String>>,
+    stats_wanted: Arc<AtomicBool>,
+    frozen: Arc<AtomicBool>,
+    reload_requested: Arc<AtomicBool>,
+    /// Ends the background loader once the manager is dropped, e.g. when another database is opened.
+    stopped: Arc<AtomicBool>,
 }
 
 impl<T: DataLoader + Send + 'static + Clone> DataManager<T> {
     pub fn new(loader: T) -> Self {
         let (tx, rx) = mpsc::channel();
+        let (stats_tx, stats_rx) = mpsc::channel();
         Self {
             loader,
             records: HashMap::new(),
             headers: HashMap::new(),
+            type_stats: HashMap::new(),
+            stats: None,
+            sequence: None,
+            load_error: None,
+            generation: 0,
             tx,
             rx,
+            stats_tx,
+            stats_rx,
+            stats_wanted: Arc::new(AtomicBool::new(false)),
+            frozen: Arc::new(AtomicBool::new(false)),
+            reload_requested: Arc::new(AtomicBool::new(false)),
+            stopped: Arc::new(AtomicBool::new(false)),
         }
     }
 
     pub fn start_background_loading(&self) {
         let loader = self.loader.clone();
         let tx = self.tx.clone();
+        let stats_tx = self.stats_tx.clone();
+        let stats_wanted = self.stats_wanted.clone();
+        let frozen = self.frozen.clone();
+        let reload_requested = self.reload_requested.clone();
+        let stopped = self.stopped.clone();
         thread::spawn(move || {
-            loop {
-                if loader.has_changed() {
-                    let records = loader.load_records();
-                    if tx.send(records).is_err() {
-                        break;
+            // The handle and snapshot of each load are held until the next one, so a frozen
+            // view keeps reading at the sequence number it shows
+            'reload: while !stopped.load(Ordering::Relaxed) {
+                let db = loader.open();
+                let snapshot = db.as_ref().ok().filter(|_| loader.uses_snapshots()).map(DB::snapshot);
+                let loaded = match &db {
+                    Ok(db) => loader.load_records(db, snapshot.as_ref()),
+                    Err(e) => LoadedData { error: Some(e.clone()), ..Default::default() },
+                };
+                if tx.send(loaded).is_err() {
+                    break;
+                }
+                loop {
+                    if stats_wanted.load(Ordering::Relaxed) && stats_tx.send(loader.load_stats()).is_err() {
+                        break 'reload;
+                    }
+                    thread::sleep(Duration::from_millis(500));
+                    if stopped.load(Ordering::Relaxed) {
+                        break 'reload;
+                    }
+                    if reload_requested.swap(false, Ordering::Relaxed) || (!frozen.load(Ordering::Relaxed) && loader.has_changed()) {
+                        continue 'reload;
                     }
                 }
-                thread::sleep(Duration::from_millis(500));
             }
         });
     }
 
+    /// Turns property collection for the stats screen on or off in the background loader.
+    pub fn set_stats_wanted(&self, wanted: bool) {
+        self.stats_wanted.store(wanted, Ordering::Relaxed);
+    }
+
+    /// Freezes the view on the snapshot the loader holds; new writes are not picked up until a reload.
+    pub fn set_frozen(&self, frozen: bool) {
+        self.frozen.store(frozen, Ordering::Relaxed);
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.frozen.load(Ordering::Relaxed)
+    }
+
+    /// Reloads from a fresh snapshot on the next tick, even while frozen.
+    pub fn request_reload(&self) {
+        self.reload_requested.store(true, Ordering::Relaxed);
+    }
+
     pub fn try_recv(&mut self) -> bool {
-        if let Ok(new_records) = self.rx.try_recv() {
-            self.records = new_records;
-            self.collect_headers();
+        if let Ok(stats) = self.stats_rx.try_recv() {
+            self.stats = Some(stats);
+        }
+        if let Ok(loaded) = self.rx.try_recv() {
+            self.apply_loaded(loaded);
             true
         } else {
             false
         }
     }
 
+    pub fn apply_loaded(&mut self, loaded: LoadedData) {
+        self.records = loaded.records;
+        self.type_stats = loaded.type_stats;
+        self.sequence = loaded.sequence;
+        self.load_error = loaded.error;
+        self.generation += 1;
+        self.collect_headers();
+    }
+
     pub fn get_records(&self) -> &HashMap<String, Vec<Record>> {
         &self.records
     }
@@ -102,6 +318,7 @@ impl<T: DataLoader + Send + 'static + Clone> DataManager<T> {
         if let Some(records) = self.records.get_mut(table) {
             records.retain(|r| r.key != key);
         }
+        self.generation += 1;
     }
 
     pub fn collect_headers(&mut self) {
@@ -116,6 +333,12 @@ impl<T: DataLoader + Send + 'static + Clone> DataManager<T> {
                 }
             }
             let mut headers = vec!["key".to_string()];
+            if records.iter().any(|r| r.written_at.is_some()) {
+                headers.push(WRITTEN_COLUMN.to_string());
+            }
+            if records.iter().any(|r| r.expires_at.is_some()) {
+                headers.push(EXPIRES_COLUMN.to_string());
+            }
             let mut keys: Vec<String> = all_keys.into_iter().collect();
             keys.sort();
             headers.extend(keys);
@@ -125,35 +348,41 @@ impl<T: DataLoader + Send + 'static + Clone> DataManager<T> {
 
 }
 
+impl<T: DataLoader> Drop for DataManager<T> {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
 #[derive(Clone)]
 pub struct PaginatedDataLoader {
     db_path: String,
+    overrides: Overrides,
+    rule_set: RuleSet,
     last_load_time: SystemTime,
 }
 
 impl PaginatedDataLoader {
-    pub fn new(db_path: String) -> Self {
-        Self { db_path, last_load_time: SystemTime::UNIX_EPOCH }
+    pub fn new(db_path: String, overrides: Overrides, rule_set: RuleSet) -> Self {
+        Self { db_path, overrides, rule_set, last_load_time: SystemTime::UNIX_EPOCH }
     }
 }
 
 impl DataLoader for PaginatedDataLoader {
-    fn load_records(&self) -> HashMap<String, Vec<Record>> {
-        let mut opts = Options::default();
-        opts.create_if_missing(false);
-        let mut records: HashMap<String, Vec<Record>> = HashMap::new();
-        if let Ok(db) = DB::open_for_read_only(&opts, &self.db_path, false) {
-            let iter = db.iterator(IteratorMode::Start);
-            for item in iter {
-                if let Ok((key_bytes, value_bytes)) = item {
-                    let key = String::from_utf8_lossy(&key_bytes).to_string();
-                    let value = value_bytes.to_vec();
-                    let record = deserialize_record(&key, &value);
-                    records.entry(record.record_type.clone()).or_insert_with(Vec::new).push(record);
-                }
-            }
-        }
-        records
+    fn open(&self) -> Result<DB, String> {
+        open_read_only(&self.db_path, &self.overrides).map(|(db, _)| db)
+    }
+
+    fn uses_snapshots(&self) -> bool {
+        self.overrides.mode != OpenMode::Secondary
+    }
+
+    fn load_records(&self, db: &DB, snapshot: Option<&Snapshot>) -> LoadedData {
+        read_snapshot(&self.db_path, &self.overrides, &self.rule_set, db, snapshot)
+    }
+
+    fn load_stats(&self) -> Result<Vec<CfStats>, String> {
+        collect_stats(&self.db_path, &self.overrides)
     }
 
     fn has_changed(&self) -> bool {
@@ -0,0 +1,300 @@
+use crate::models::{deserialize_record, Record, RuleSet};
+use crate::options::{KeyOrder, Overrides};
+use rocksdb::{IteratorMode, DB};
+use serde_json::Value;
+use std::cmp::Ordering;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DiffKind {
+    OnlyLeft,
+    OnlyRight,
+    Changed,
+}
+
+impl DiffKind {
+    pub fn marker(self) -> char {
+        match self {
+            DiffKind::OnlyLeft => '-',
+            DiffKind::OnlyRight => '+',
+            DiffKind::Changed => '~',
+        }
+    }
+}
+
+/// A key that is missing on one side or whose value differs.
+#[derive(Clone, Debug)]
+pub struct DiffEntry {
+    /// Column family of the key.
+    pub cf: String,
+    pub key: String,
+    pub kind: DiffKind,
+    pub left: Option<Vec<u8>>,
+    pub right: Option<Vec<u8>>,
+}
+
+/// One differing field of a changed record, addressed by a dotted path into the decoded JSON,
+/// with array elements as `[index]`.
+#[derive(Clone, Debug)]
+pub struct FieldChange {
+    pub path: String,
+    pub left: Option<Value>,
+    pub right: Option<Value>,
+}
+
+impl DiffEntry {
+    /// The key, prefixed with its column family unless that is the default one.
+    pub fn label(&self) -> String {
+        if self.cf == "default" { self.key.clone() } else { format!("[{}] {}", self.cf, self.key) }
+    }
+
+    pub fn field_changes(&self, rule_set: &RuleSet) -> Vec<FieldChange> {
+        let (Some(left), Some(right)) = (&self.left, &self.right) else { return Vec::new() };
+        let left = deserialize_record(rule_set, &self.key, left).data;
+        let right = deserialize_record(rule_set, &self.key, right).data;
+        field_changes(&left, &right)
+    }
+}
+
+pub fn field_changes(left: &Value, right: &Value) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+    diff_values("", Some(left), Some(right), &mut changes);
+    changes
+}
+
+fn diff_values(path: &str, left: Option<&Value>, right: Option<&Value>, changes: &mut Vec<FieldChange>) {
+    match (left, right) {
+        (Some(Value::Object(l)), Some(Value::Object(r))) => {
+            let mut fields: Vec<&String> = l.keys().chain(r.keys()).collect();
+            fields.sort();
+            fields.dedup();
+            for field in fields {
+                let child = if path.is_empty() { field.clone() } else { format!("{}.{}", path, field) };
+                diff_values(&child, l.get(field), r.get(field), changes);
+            }
+        }
+        (Some(Value::Array(l)), Some(Value::Array(r))) => {
+            for i in 0..l.len().max(r.len()) {
+                diff_values(&format!("{}[{}]", path, i), l.get(i), r.get(i), changes);
+            }
+        }
+        (l, r) if l != r => changes.push(FieldChange {
+            path: if path.is_empty() { "(value)".to_string() } else { path.to_string() },
+            left: l.cloned(),
+            right: r.cloned(),
+        }),
+        _ => {}
+    }
+}
+
+/// The differences between two databases.
+#[derive(Clone, Debug, Default)]
+pub struct DatabaseDiff {
+    pub entries: Vec<DiffEntry>,
+    /// Column families that exist in only one of the databases; their keys are not compared.
+    pub only_left_cfs: Vec<String>,
+    pub only_right_cfs: Vec<String>,
+}
+
+impl DatabaseDiff {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty() && self.only_left_cfs.is_empty() && self.only_right_cfs.is_empty()
+    }
+}
+
+/// Compares every column family the two databases share key by key, and lists the ones only one of them has.
+/// Both sides are opened with the same `overrides`.
+pub fn diff_databases(left_path: &str, right_path: &str, overrides: &Overrides) -> Result<DatabaseDiff, String> {
+    let (left_db, left_cfs) = crate::data::open_read_only(left_path, overrides).map_err(|e| format!("{}: {}", left_path, e))?;
+    let (right_db, right_cfs) = crate::data::open_read_only(right_path, overrides).map_err(|e| format!("{}: {}", right_path, e))?;
+    let mut diff = DatabaseDiff {
+        only_left_cfs: left_cfs.iter().filter(|cf| !right_cfs.contains(cf)).cloned().collect(),
+        only_right_cfs: right_cfs.iter().filter(|cf| !left_cfs.contains(cf)).cloned().collect(),
+        ..Default::default()
+    };
+    for cf in left_cfs.iter().filter(|cf| right_cfs.contains(cf)) {
+        // Both sides are walked in the column family's own key order, which has to be the same on both
+        let order = crate::options::key_order(left_path, cf, overrides)?;
+        let right_order = crate::options::key_order(right_path, cf, overrides)?;
+        if order != right_order {
+            return Err(format!("{} is ordered {} on the left but {} on the right", cf, order.label(), right_order.label()));
+        }
+        diff_cf(&left_db, &right_db, cf, order, &mut diff.entries)?;
+    }
+    Ok(diff)
+}
+
+/// Walks one column family of both databases in key order and collects every key that is not identical on both sides.
+fn diff_cf(left_db: &DB, right_db: &DB, cf: &str, order: KeyOrder, entries: &mut Vec<DiffEntry>) -> Result<(), String> {
+    let left_cf = left_db.cf_handle(cf).ok_or_else(|| format!("no column family {}", cf))?;
+    let right_cf = right_db.cf_handle(cf).ok_or_else(|| format!("no column family {}", cf))?;
+    let mut left_iter = left_db.iterator_cf(left_cf, IteratorMode::Start);
+    let mut right_iter = right_db.iterator_cf(right_cf, IteratorMode::Start);
+    let mut left = left_iter.next().transpose().map_err(|e| e.to_string())?;
+    let mut right = right_iter.next().transpose().map_err(|e| e.to_string())?;
+    let entry = |key: &[u8], kind, left: Option<&[u8]>, right: Option<&[u8]>| DiffEntry {
+        cf: cf.to_string(),
+        key: String::from_utf8_lossy(key).to_string(),
+        kind,
+        left: left.map(<[u8]>::to_vec),
+        right: right.map(<[u8]>::to_vec),
+    };
+
+    loop {
+        let order = match (&left, &right) {
+            (None, None) => break,
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some((l, _)), Some((r, _))) => order.compare(l, r),
+        };
+        match order {
+            Ordering::Less => {
+                let (key, value) = left.take().unwrap();
+                entries.push(entry(&key, DiffKind::OnlyLeft, Some(&value), None));
+                left = left_iter.next().transpose().map_err(|e| e.to_string())?;
+            }
+            Ordering::Greater => {
+                let (key, value) = right.take().unwrap();
+                entries.push(entry(&key, DiffKind::OnlyRight, None, Some(&value)));
+                right = right_iter.next().transpose().map_err(|e| e.to_string())?;
+            }
+            Ordering::Equal => {
+                let (key, left_value) = left.take().unwrap();
+                let (_, right_value) = right.take().unwrap();
+                if left_value != right_value {
+                    entries.push(entry(&key, DiffKind::Changed, Some(&left_value), Some(&right_value)));
+                }
+                left = left_iter.next().transpose().map_err(|e| e.to_string())?;
+                right = right_iter.next().transpose().map_err(|e| e.to_string())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn field_value(value: &Option<Value>) -> String {
+    match value {
+        Some(value) => value.to_string(),
+        None => "(missing)".to_string(),
+    }
+}
+
+/// Prints the diff in a unified-diff-like format: `-` only left, `+` only right, `~` changed.
+/// Changed values are decoded with `rule_set` to list the fields that differ.
+pub fn print_report(diff: &DatabaseDiff, rule_set: &RuleSet) {
+    for cf in &diff.only_left_cfs {
+        println!("- column family {}", cf);
+    }
+    for cf in &diff.only_right_cfs {
+        println!("+ column family {}", cf);
+    }
+    let entries = &diff.entries;
+    for entry in entries {
+        println!("{} {}", entry.kind.marker(), entry.label());
+        for change in entry.field_changes(rule_set) {
+            println!("    {}: {} -> {}", change.path, field_value(&change.left), field_value(&change.right));
+        }
+    }
+    let count = |kind| entries.iter().filter(|e| e.kind == kind).count();
+    println!(
+        "{} only in left, {} only in right, {} changed",
+        count(DiffKind::OnlyLeft),
+        count(DiffKind::OnlyRight),
+        count(DiffKind::Changed)
+    );
+}
+
+/// The split diff view: the differing keys on top, the two decoded values side by side below.
+pub struct DiffState {
+    pub left_path: String,
+    pub right_path: String,
+    pub entries: Vec<DiffEntry>,
+    pub only_left_cfs: Vec<String>,
+    pub only_right_cfs: Vec<String>,
+    /// Decodes both sides' values.
+    pub rule_set: RuleSet,
+    pub index: usize,
+    /// Only entries of this kind are listed when set.
+    pub filter: Option<DiffKind>,
+}
+
+impl DiffState {
+    pub fn new(left_path: &str, right_path: &str, diff: DatabaseDiff, rule_set: RuleSet) -> Self {
+        Self {
+            left_path: left_path.to_string(),
+            right_path: right_path.to_string(),
+            entries: diff.entries,
+            only_left_cfs: diff.only_left_cfs,
+            only_right_cfs: diff.only_right_cfs,
+            rule_set,
+            index: 0,
+            filter: None,
+        }
+    }
+
+    pub fn visible(&self) -> Vec<&DiffEntry> {
+        self.entries.iter().filter(|e| self.filter.is_none_or(|kind| e.kind == kind)).collect()
+    }
+
+    pub fn selected(&self) -> Option<&DiffEntry> {
+        self.visible().get(self.index).copied()
+    }
+
+    pub fn cycle_filter(&mut self) {
+        self.filter = match self.filter {
+            None => Some(DiffKind::OnlyLeft),
+            Some(DiffKind::OnlyLeft) => Some(DiffKind::OnlyRight),
+            Some(DiffKind::OnlyRight) => Some(DiffKind::Changed),
+            Some(DiffKind::Changed) => None,
+        };
+        self.index = 0;
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        let last = self.visible().len().saturating_sub(1);
+        self.index = self.index.saturating_add_signed(delta).min(last);
+    }
+}
+
+/// Two records of one table compared with each other. Values that are not JSON on either
+/// side are compared byte by byte instead of field by field.
+pub struct RecordDiff {
+    pub left: Record,
+    pub right: Record,
+    /// The changed fields, or `None` when the values are compared as bytes.
+    pub changes: Option<Vec<FieldChange>>,
+    pub scroll: u16,
+}
+
+impl RecordDiff {
+    pub fn new(left: Record, right: Record) -> Self {
+        let decodable = |record: &Record| serde_json::from_slice::<Value>(&record.raw_data).is_ok();
+        let changes = (decodable(&left) && decodable(&right)).then(|| field_changes(&left.data, &right.data));
+        Self { left, right, changes, scroll: 0 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn paths(left: Value, right: Value) -> Vec<String> {
+        field_changes(&left, &right).into_iter().map(|c| c.path).collect()
+    }
+
+    #[test]
+    fn nested_objects_are_compared_per_field() {
+        assert_eq!(paths(json!({"a": 1, "b": {"c": 2}}), json!({"a": 1, "b": {"c": 3, "d": 4}})), ["b.c", "b.d"]);
+    }
+
+    #[test]
+    fn arrays_are_compared_per_index() {
+        assert_eq!(paths(json!({"tags": ["x", {"n": 1}]}), json!({"tags": ["x", {"n": 2}, "y"]})), ["tags[1].n", "tags[2]"]);
+        assert_eq!(paths(json!([1, 2]), json!([1, 3])), ["[1]"]);
+    }
+
+    #[test]
+    fn identical_values_have_no_changes() {
+        assert!(paths(json!({"a": [1, {"b": null}]}), json!({"a": [1, {"b": null}]})).is_empty());
+    }
+}
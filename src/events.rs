@@ -1,39 +1,318 @@
-use crate::app::{App, Focus};
+use crate::app::{App, Focus, View, COLUMN_SPACING};
+use crate::admin::{AdminAction, AdminOp, AdminStage};
+use crate::backup::PathAction;
+use crate::clipboard::CopyTarget;
+use crate::keymap::{Action, Input};
+use crate::tabs::Tabs;
 use crossterm::event::{Event, KeyCode, KeyModifiers, MouseButton, MouseEventKind};
-use rocksdb::Options;
 use std::thread;
 use std::time::Duration;
 
-pub fn handle_event(event: Event, app: &mut App, db_path: &str, chunks: &[ratatui::layout::Rect]) {
-    if let Some(_) = app.show_raw_data {
-        if let Event::Key(key) = event {
-            if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
-                std::process::exit(0);
-            } else if key.code == KeyCode::Esc {
-                app.show_raw_data = None;
-            }
+/// Handles the keys that open, close and switch tabs; returns whether the input was one of them.
+pub fn handle_tab_input(input: &Input, tabs: &mut Tabs) -> bool {
+    match (input.action, input.key.code) {
+        (Some(Action::NewTab), _) => tabs.open_tab(),
+        (Some(Action::CloseTab), _) => tabs.close_active(),
+        (Some(Action::PrevTab), _) => tabs.cycle(-1),
+        (Some(Action::NextTab), _) => tabs.cycle(1),
+        (None, KeyCode::Char(c @ '1'..='9')) if input.key.modifiers.contains(KeyModifiers::ALT) => tabs.select(c as usize - '1' as usize),
+        _ => return false,
+    }
+    true
+}
+
+/// Handles a key press once the keymap has resolved it.
+pub fn handle_input(input: Input, app: &mut App) {
+    if app.profile_picker.is_some() {
+        handle_profile_picker_input(input, app);
+    } else if app.record_diff.is_some() {
+        handle_record_diff_input(input, app);
+    } else if app.hex_view.is_some() {
+        handle_hex_view_input(input, app);
+    } else if app.show_raw_data.is_some() {
+        handle_text_popup_input(input, app);
+    } else if app.column_chooser.is_some() {
+        handle_column_chooser_input(input, app);
+    } else if app.admin_menu.is_some() {
+        handle_admin_menu_input(input, app);
+    } else if app.copy_menu {
+        app.copy_menu = false;
+        if let KeyCode::Char(c) = input.key.code && let Some(target) = CopyTarget::from_shortcut(c) {
+            app.copy_selected(target);
+        }
+    } else {
+        handle_browse_input(input, app);
+    }
+}
+
+/// Handles everything but key presses, which go through `handle_input`.
+pub fn handle_event(event: Event, app: &mut App, chunks: &[ratatui::layout::Rect]) {
+    let Event::Mouse(mouse_event) = event else { return };
+    if app.profile_picker.is_some() {
+        return;
+    }
+    let scroll = match mouse_event.kind {
+        MouseEventKind::ScrollUp => -3,
+        MouseEventKind::ScrollDown => 3,
+        _ => 0,
+    };
+    if let Some(diff) = app.record_diff.as_mut() {
+        diff.scroll = diff.scroll.saturating_add_signed(scroll as i16);
+    } else if let Some(view) = app.hex_view.as_mut() {
+        if scroll != 0 {
+            view.scroll_by(scroll);
+        }
+    } else if app.show_raw_data.is_some() {
+        if scroll != 0 {
+            app.scroll_text_popup(scroll as i32);
+        }
+    } else if app.column_chooser.is_none() && app.admin_menu.is_none() && !app.copy_menu {
+        handle_mouse_event(mouse_event, app, chunks);
+    }
+}
+
+fn handle_profile_picker_input(input: Input, app: &mut App) {
+    if input.action == Some(Action::Quit) {
+        app.should_quit = true;
+        return;
+    }
+    let Some(picker) = app.profile_picker.as_mut() else { return };
+    if let Some(text) = picker.path_input.as_mut() {
+        match (input.action, input.key.code) {
+            (Some(Action::Back), _) => picker.path_input = None,
+            (Some(Action::Select), _) => {}
+            (_, KeyCode::Backspace) => { text.pop(); }
+            (None, KeyCode::Char(c)) => text.push(c),
+            _ => return,
+        }
+        if input.action != Some(Action::Select) {
             return;
-        } else if let Event::Mouse(mouse_event) = event {
-            if mouse_event.kind == MouseEventKind::Down(MouseButton::Left) {
+        }
+    }
+    match input.action {
+        Some(Action::Back) if picker.startup => app.should_quit = true,
+        Some(Action::Back) => app.profile_picker = None,
+        Some(Action::Up) => picker.select(-1),
+        Some(Action::Down) => picker.select(1),
+        Some(Action::Select) => {
+            let Some(choice) = picker.choose() else { return };
+            if let Err(e) = app.open_choice(choice) {
+                app.status_message = Some(format!("cannot open: {}", e));
             }
-            return;
         }
+        _ => {}
     }
+}
 
-    if let Event::Key(key) = event {
-        handle_key_event(key, app, db_path);
-    } else if let Event::Mouse(mouse_event) = event {
-        handle_mouse_event(mouse_event, app, chunks);
+fn handle_record_diff_input(input: Input, app: &mut App) {
+    let Some(diff) = app.record_diff.as_mut() else { return };
+    match input.action {
+        Some(Action::Back) => app.record_diff = None,
+        Some(Action::Quit) => app.should_quit = true,
+        Some(Action::Up) => diff.scroll = diff.scroll.saturating_sub(1),
+        Some(Action::Down) => diff.scroll = diff.scroll.saturating_add(1),
+        Some(Action::PageUp) => diff.scroll = diff.scroll.saturating_sub(20),
+        Some(Action::PageDown) => diff.scroll = diff.scroll.saturating_add(20),
+        Some(Action::Top) => diff.scroll = 0,
+        _ => {}
     }
 }
 
-fn handle_key_event(key: crossterm::event::KeyEvent, app: &mut App, db_path: &str) {
-    if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+fn handle_admin_menu_input(input: Input, app: &mut App) {
+    let Some(menu) = app.admin_menu.as_mut() else { return };
+    match &mut menu.stage {
+        AdminStage::Menu => match input.action {
+            Some(Action::Back | Action::Admin) => app.admin_menu = None,
+            Some(Action::Up) => menu.action_index = menu.action_index.saturating_sub(1),
+            Some(Action::Down) => menu.action_index = (menu.action_index + 1).min(AdminAction::ALL.len() - 1),
+            Some(Action::Left) => menu.cf_index = menu.cf_index.saturating_sub(1),
+            Some(Action::Right) => menu.cf_index = (menu.cf_index + 1).min(menu.cf_names.len().saturating_sub(1)),
+            Some(Action::Select) => app.choose_admin_action(),
+            _ => {}
+        },
+        AdminStage::Input { action, fields, field } => match (input.action, input.key.code) {
+            (Some(Action::Back), _) => menu.stage = AdminStage::Menu,
+            (Some(Action::NextFocus), _) => *field = (*field + 1) % fields.len(),
+            (Some(Action::PrevFocus), _) => *field = (*field + fields.len() - 1) % fields.len(),
+            (_, KeyCode::Backspace) => {
+                fields[*field].1.pop();
+            }
+            (None, KeyCode::Char(c)) => fields[*field].1.push(c),
+            (Some(Action::Select), _) => {
+                // A field that is still missing or a mistyped name keeps the fields open for another try
+                match AdminOp::from_fields(&menu.cf_names[menu.cf_index], *action, fields) {
+                    Ok(op) => menu.stage = AdminStage::Confirm(op),
+                    Err(e) => app.status_message = Some(e),
+                }
+            }
+            _ => {}
+        },
+        AdminStage::Confirm(_) => match (input.action, input.key.code) {
+            (_, KeyCode::Char('y')) => app.confirm_admin_op(),
+            (_, KeyCode::Char('n')) | (Some(Action::Back), _) => menu.stage = AdminStage::Menu,
+            _ => {}
+        },
+    }
+}
+
+fn handle_text_popup_input(input: Input, app: &mut App) {
+    let page = app.raw_page_height.max(1) as i32;
+    match (input.action, input.key.code) {
+        (Some(Action::Quit), _) => app.should_quit = true,
+        (Some(Action::Back), _) => {
+            app.show_raw_data = None;
+            app.status_message = None;
+        }
+        (Some(Action::Up), _) => app.scroll_text_popup(-1),
+        (Some(Action::Down), _) => app.scroll_text_popup(1),
+        (Some(Action::PageUp), _) => app.scroll_text_popup(-page),
+        (Some(Action::PageDown), _) => app.scroll_text_popup(page),
+        (Some(Action::Top), _) => app.raw_scroll = 0,
+        (Some(Action::Bottom), _) => app.raw_scroll = u16::MAX,
+        (Some(Action::Left), _) => app.raw_scroll_x = app.raw_scroll_x.saturating_sub(4),
+        (Some(Action::Right), _) if !app.raw_wrap => app.raw_scroll_x = app.raw_scroll_x.saturating_add(4),
+        (Some(Action::Copy), _) => {
+            if let Some(text) = app.show_raw_data.clone() {
+                app.copy_text("popup text", &text);
+            }
+        }
+        (_, KeyCode::Char('w')) => {
+            app.raw_wrap = !app.raw_wrap;
+            app.raw_scroll_x = 0;
+        }
+        (_, KeyCode::Char('m')) => app.raw_maximized = !app.raw_maximized,
+        _ => {}
+    }
+}
+
+fn handle_column_chooser_input(input: Input, app: &mut App) {
+    let Some(chooser) = app.column_chooser.as_mut() else { return };
+    if let Some(text) = chooser.width_input.as_mut() {
+        match (input.action, input.key.code) {
+            (Some(Action::Back), _) => chooser.width_input = None,
+            (Some(Action::Select), _) => chooser.submit_width(),
+            (_, KeyCode::Backspace) => { text.pop(); }
+            (None, KeyCode::Char(c)) if c.is_ascii_digit() => text.push(c),
+            _ => {}
+        }
+        return;
+    }
+    let shift = input.key.modifiers.contains(KeyModifiers::SHIFT);
+    match (input.action, input.key.code) {
+        (Some(Action::Back), _) => app.column_chooser = None,
+        (Some(Action::Select), _) => app.apply_column_chooser(),
+        (None, KeyCode::Up) if shift => chooser.move_selected(-1),
+        (None, KeyCode::Down) if shift => chooser.move_selected(1),
+        (_, KeyCode::Char('K')) => chooser.move_selected(-1),
+        (_, KeyCode::Char('J')) => chooser.move_selected(1),
+        (Some(Action::Up), _) => chooser.select(-1),
+        (Some(Action::Down), _) => chooser.select(1),
+        (_, KeyCode::Char(' ')) => chooser.toggle_selected(),
+        (_, KeyCode::Char('w')) => chooser.width_input = Some(String::new()),
+        (_, KeyCode::Char('t')) => chooser.cycle_time_format(),
+        _ => {}
+    }
+}
+
+fn handle_hex_view_input(input: Input, app: &mut App) {
+    if input.action == Some(Action::Quit) {
         app.should_quit = true;
         return;
     }
+    let Some(view) = app.hex_view.as_mut() else { return };
+    if let Some(text) = view.search_input.as_mut() {
+        match (input.action, input.key.code) {
+            (Some(Action::Back), _) => view.search_input = None,
+            (Some(Action::Select), _) => view.submit_search(),
+            (_, KeyCode::Backspace) => { text.pop(); }
+            (None, KeyCode::Char(c)) => text.push(c),
+            _ => {}
+        }
+        return;
+    }
+    // Shift+arrow keys are not bound, so they arrive as plain keys and extend the selection
+    let extend = input.key.modifiers.contains(KeyModifiers::SHIFT);
+    let line = crate::hexdump::BYTES_PER_LINE as isize;
+    let page = view.page_lines.max(1) as isize * line;
+    view.message = None;
+    match (input.action, input.key.code) {
+        (Some(Action::Back), _) => {
+            if view.anchor.is_some() {
+                view.anchor = None;
+            } else {
+                app.hex_view = None;
+            }
+        }
+        (Some(Action::Left), _) | (None, KeyCode::Left) => view.move_cursor(-1, extend),
+        (Some(Action::Right), _) | (None, KeyCode::Right) => view.move_cursor(1, extend),
+        (Some(Action::Up), _) | (None, KeyCode::Up) => view.move_cursor(-line, extend),
+        (Some(Action::Down), _) | (None, KeyCode::Down) => view.move_cursor(line, extend),
+        (Some(Action::PageUp), _) | (None, KeyCode::PageUp) => view.move_cursor(-page, extend),
+        (Some(Action::PageDown), _) | (None, KeyCode::PageDown) => view.move_cursor(page, extend),
+        (Some(Action::Top), _) | (None, KeyCode::Home) => view.move_cursor(-(view.cursor as isize), extend),
+        (Some(Action::Bottom), _) | (None, KeyCode::End) => view.move_cursor(view.data.len() as isize, extend),
+        (Some(Action::Copy), _) => {
+            let (start, end) = view.selection();
+            let bytes = view.data.get(start..=end).unwrap_or(&[]);
+            let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+            view.message = Some(match crate::clipboard::copy_to_clipboard(&hex) {
+                Ok(_) => format!("copied {} bytes as hex", bytes.len()),
+                Err(e) => format!("copy failed: {}", e),
+            });
+        }
+        (Some(Action::Search), _) => view.search_input = Some(String::new()),
+        (Some(Action::NextMatch), _) => view.find(true),
+        (Some(Action::PrevMatch), _) => view.find(false),
+        (_, KeyCode::Char('v')) => view.toggle_selection(),
+        _ => {}
+    }
+}
+
+fn handle_browse_input(input: Input, app: &mut App) {
+    app.status_message = None;
+
+    match input.action {
+        Some(Action::Quit) => app.should_quit = true,
+        Some(Action::Freeze) => {
+            let frozen = !app.data_manager.is_frozen();
+            app.data_manager.set_frozen(frozen);
+            app.status_message = Some(if frozen { "view frozen on the current snapshot" } else { "following the latest writes" }.to_string());
+        }
+        Some(Action::Refresh) => {
+            app.data_manager.request_reload();
+            app.status_message = Some("refreshing to the latest sequence number".to_string());
+        }
+        Some(Action::OpenDatabase) => app.open_profile_picker(),
+        Some(Action::Stats) => app.set_view(if app.view == View::Stats { View::Browse } else { View::Stats }),
+        Some(Action::Charts) if app.view == View::Charts => app.set_view(View::Browse),
+        Some(Action::Charts) => app.open_charts(),
+        Some(Action::SstFiles) if app.view == View::Sst => app.set_view(View::Browse),
+        Some(Action::SstFiles) => app.open_sst_files(),
+        Some(Action::Wal) if app.view == View::Wal => app.set_view(View::Browse),
+        Some(Action::Wal) => app.open_wal(),
+        Some(Action::Diff) if app.diff.is_some() => app.set_view(if app.view == View::Diff { View::Browse } else { View::Diff }),
+        Some(Action::Admin) => app.open_admin_menu(),
+        Some(Action::Checkpoint) => app.quick_checkpoint(),
+        Some(Action::Backups) if app.view == View::Backups => app.set_view(View::Browse),
+        Some(Action::Backups) => app.open_backups(),
+        _ => handle_view_input(input, app),
+    }
+}
 
-    if key.code == KeyCode::Esc && (app.focus == Focus::Table || app.focus == Focus::Input || app.focus == Focus::Pages) {
+fn handle_view_input(input: Input, app: &mut App) {
+    match app.view {
+        View::Browse => handle_records_input(input, app),
+        View::Stats => handle_stats_input(input, app),
+        View::Backups => handle_backups_input(input, app),
+        View::Diff => handle_diff_input(input, app),
+        View::Wal => handle_wal_input(input, app),
+        View::Sst => handle_sst_input(input, app),
+        View::Charts => handle_charts_input(input, app),
+    }
+}
+
+fn handle_records_input(input: Input, app: &mut App) {
+    if input.action == Some(Action::Back) && (app.focus == Focus::Table || app.focus == Focus::Input || app.focus == Focus::Pages) {
         app.focus = Focus::TableSelect;
         app.selected_table = None;
         app.selected_row = None;
@@ -41,16 +320,128 @@ fn handle_key_event(key: crossterm::event::KeyEvent, app: &mut App, db_path: &st
     }
 
     match app.focus {
-        Focus::Input => handle_input_key(key, app),
-        Focus::TableSelect => handle_table_select_key(key, app),
-        Focus::Table => handle_table_key(key, app, db_path),
-        Focus::Pages => handle_pages_key(key, app),
+        Focus::Input => handle_filter_input(input, app),
+        Focus::TableSelect => handle_table_select_input(input, app),
+        Focus::Table => handle_table_input(input, app),
+        Focus::Pages => handle_pages_input(input, app),
+    }
+}
+
+fn handle_stats_input(input: Input, app: &mut App) {
+    let cf_count = app.data_manager.stats.as_ref().and_then(|s| s.as_ref().ok()).map_or(0, |s| s.len());
+    match input.action {
+        Some(Action::Back) => app.set_view(View::Browse),
+        Some(Action::Up) => {
+            app.stats_cf_index = app.stats_cf_index.saturating_sub(1);
+            app.stats_scroll = 0;
+        }
+        Some(Action::Down) if app.stats_cf_index + 1 < cf_count => {
+            app.stats_cf_index += 1;
+            app.stats_scroll = 0;
+        }
+        Some(Action::PageUp) => app.stats_scroll = app.stats_scroll.saturating_sub(10),
+        Some(Action::PageDown) => app.stats_scroll = app.stats_scroll.saturating_add(10),
+        _ => {}
+    }
+}
+
+fn handle_charts_input(input: Input, app: &mut App) {
+    if input.action == Some(Action::Back) {
+        app.set_view(View::Browse);
+        return;
+    }
+    let Some(charts) = app.charts.as_mut() else { return };
+    let query = &mut charts.query;
+    match (input.action, input.key.code) {
+        (Some(Action::Left), _) if query.segment > 1 => query.segment -= 1,
+        (Some(Action::Right), _) => query.segment += 1,
+        (_, KeyCode::Char('+')) => query.prefix_len += 1,
+        (_, KeyCode::Char('-')) => query.prefix_len = query.prefix_len.saturating_sub(1),
+        (_, KeyCode::Char('r')) => {}
+        _ => return,
     }
+    charts.refresh(&app.db_path, &app.overrides);
 }
 
-fn handle_input_key(key: crossterm::event::KeyEvent, app: &mut App) {
-    match key.code {
-        KeyCode::Tab => {
+fn handle_sst_input(input: Input, app: &mut App) {
+    let file_count = app.sst_files.as_ref().and_then(|f| f.as_ref().ok()).map_or(0, |f| f.len());
+    match (input.action, input.key.code) {
+        (Some(Action::Back), _) => app.set_view(View::Browse),
+        (Some(Action::Up), _) => app.sst_index = app.sst_index.saturating_sub(1),
+        (Some(Action::Down), _) if app.sst_index + 1 < file_count => app.sst_index += 1,
+        (_, KeyCode::Char('r')) => app.open_sst_files(),
+        _ => {}
+    }
+}
+
+fn handle_backups_input(input: Input, app: &mut App) {
+    let Some(backups) = app.backups.as_mut() else { return };
+    if let Some((_, text)) = backups.prompt.as_mut() {
+        match (input.action, input.key.code) {
+            (Some(Action::Back), _) => backups.prompt = None,
+            (Some(Action::Select), _) => app.submit_backup_prompt(),
+            (_, KeyCode::Backspace) => {
+                text.pop();
+            }
+            (None, KeyCode::Char(c)) => text.push(c),
+            _ => {}
+        }
+        return;
+    }
+    let count = backups.backups.as_ref().map_or(0, |b| b.len());
+    match (input.action, input.key.code) {
+        (Some(Action::Back), _) => app.set_view(View::Browse),
+        (Some(Action::Up), _) => backups.index = backups.index.saturating_sub(1),
+        (Some(Action::Down), _) => backups.index = (backups.index + 1).min(count.saturating_sub(1)),
+        (_, KeyCode::Char('n')) => app.create_backup(),
+        (_, KeyCode::Char('v')) => app.verify_backup(),
+        (_, KeyCode::Char('c')) => backups.prompt = Some((PathAction::Checkpoint, crate::backup::default_checkpoint_dir(&app.db_path))),
+        (_, KeyCode::Char('r')) => {
+            if let Some(id) = backups.selected_id() {
+                let target = format!("{}.restore-{}", app.db_path.trim_end_matches('/'), id);
+                backups.prompt = Some((PathAction::Restore(id), target));
+            }
+        }
+        (_, KeyCode::Char('o')) => backups.prompt = Some((PathAction::BackupDir, backups.backup_dir.clone())),
+        _ => {}
+    }
+}
+
+fn handle_diff_input(input: Input, app: &mut App) {
+    let Some(diff) = app.diff.as_mut() else { return };
+    match (input.action, input.key.code) {
+        (Some(Action::Back), _) => app.set_view(View::Browse),
+        (Some(Action::Up), _) => diff.move_selection(-1),
+        (Some(Action::Down), _) => diff.move_selection(1),
+        (Some(Action::PageUp), _) => diff.move_selection(-20),
+        (Some(Action::PageDown), _) => diff.move_selection(20),
+        (Some(Action::Top), _) => diff.index = 0,
+        (Some(Action::Bottom), _) => diff.move_selection(isize::MAX),
+        (_, KeyCode::Char('f')) => diff.cycle_filter(),
+        _ => {}
+    }
+}
+
+fn handle_wal_input(input: Input, app: &mut App) {
+    let row_count = app.wal.as_ref().and_then(|w| w.as_ref().ok()).map_or(0, |files| crate::wal::rows(files).len());
+    let last = row_count.saturating_sub(1);
+    match (input.action, input.key.code) {
+        (Some(Action::Back), _) => app.set_view(View::Browse),
+        (Some(Action::Up), _) => app.wal_index = app.wal_index.saturating_sub(1),
+        (Some(Action::Down), _) => app.wal_index = (app.wal_index + 1).min(last),
+        (Some(Action::PageUp), _) => app.wal_index = app.wal_index.saturating_sub(20),
+        (Some(Action::PageDown), _) => app.wal_index = (app.wal_index + 20).min(last),
+        (Some(Action::Top), _) => app.wal_index = 0,
+        (Some(Action::Bottom), _) => app.wal_index = last,
+        (Some(Action::Select), _) => app.open_wal_op(),
+        (_, KeyCode::Char('r')) => app.open_wal(),
+        _ => {}
+    }
+}
+
+fn handle_filter_input(input: Input, app: &mut App) {
+    match (input.action, input.key.code) {
+        (Some(Action::NextFocus | Action::Select), _) => {
             if app.selected_table.is_none() {
                 let mut types: Vec<String> = app.data_manager.get_records().keys().cloned().collect();
                 types.sort();
@@ -62,42 +453,42 @@ fn handle_input_key(key: crossterm::event::KeyEvent, app: &mut App) {
                 app.focus = Focus::Table;
             }
         }
-        KeyCode::Enter => {}
-        KeyCode::Backspace => {
+        (_, KeyCode::Backspace) => {
             app.input.pop();
         }
-        KeyCode::Char(c) => {
+        (None, KeyCode::Char(c)) => {
             app.input.push(c);
         }
         _ => {}
     }
 }
 
-fn handle_table_select_key(key: crossterm::event::KeyEvent, app: &mut App) {
-    match key.code {
-        KeyCode::Tab => {
+fn handle_table_select_input(input: Input, app: &mut App) {
+    match input.action {
+        Some(Action::Search) => app.focus = Focus::Input,
+        Some(Action::NextFocus) => {
             app.focus = Focus::Input;
             app.selected_table = None;
             app.selected_row = None;
         }
-        KeyCode::Enter => {
+        Some(Action::Select) => {
             let mut types: Vec<String> = app.data_manager.get_records().keys().cloned().collect();
             types.sort();
             if app.table_select_index < types.len() {
                 app.selected_table = Some(types[app.table_select_index].clone());
                 app.selected_row = Some(0);
                 app.focus = Focus::Table;
-                app.sort_column = None;
-                app.sort_ascending = true;
+                app.sort_keys.clear();
+                app.col_offset = 0;
                 app.current_page = 0;
             }
         }
-        KeyCode::Up => {
+        Some(Action::Up) => {
             if app.table_select_index > 0 {
                 app.table_select_index -= 1;
             }
         }
-        KeyCode::Down => {
+        Some(Action::Down) => {
             let mut types: Vec<String> = app.data_manager.get_records().keys().cloned().collect();
             types.sort();
             if app.table_select_index < types.len().saturating_sub(1) {
@@ -108,22 +499,26 @@ fn handle_table_select_key(key: crossterm::event::KeyEvent, app: &mut App) {
     }
 }
 
-fn handle_pages_key(key: crossterm::event::KeyEvent, app: &mut App) {
-    match key.code {
-        KeyCode::Tab => {
+fn handle_pages_input(input: Input, app: &mut App) {
+    match input.action {
+        Some(Action::Search) => {
+            app.focus = Focus::Input;
+            app.page_focus = false;
+        },
+        Some(Action::NextFocus) => {
             app.focus = Focus::Input;
             app.page_focus = false;
         },
-        KeyCode::Esc => {
+        Some(Action::Back) => {
             app.focus = Focus::Table;
             app.page_focus = false;
         },
-        KeyCode::Left => {
+        Some(Action::Left) => {
             if app.current_page > 0 {
                 app.current_page -= 1;
             }
         },
-        KeyCode::Right => {
+        Some(Action::Right) => {
             if let Some(ref table) = app.selected_table {
                 let height = app.rows_per_page.max(1) as u16;
                 let total_pages = app.get_total_pages(table, height);
@@ -144,13 +539,14 @@ fn handle_pages_key(key: crossterm::event::KeyEvent, app: &mut App) {
     }
 }
 
-fn handle_table_key(key: crossterm::event::KeyEvent, app: &mut App, db_path: &str) {
-    match key.code {
-        KeyCode::Tab => {
+fn handle_table_input(input: Input, app: &mut App) {
+    let shift = input.key.modifiers.contains(KeyModifiers::SHIFT);
+    match (input.action, input.key.code) {
+        (Some(Action::NextFocus), _) => {
             app.focus = Focus::Pages;
             app.page_focus = true;
         }
-        KeyCode::BackTab => {
+        (Some(Action::PrevFocus), _) => {
             if let Some(current_table) = &app.selected_table {
                 let mut types: Vec<String> = app.data_manager.get_records().keys().cloned().collect();
                 types.sort();
@@ -159,8 +555,8 @@ fn handle_table_key(key: crossterm::event::KeyEvent, app: &mut App, db_path: &st
                         let prev_index = current_index - 1;
                         app.selected_table = Some(types[prev_index].clone());
                         app.selected_row = Some(0);
-                        app.sort_column = None;
-                        app.sort_ascending = true;
+                        app.sort_keys.clear();
+                        app.col_offset = 0;
                     } else {
                         app.focus = Focus::Input;
                     }
@@ -169,17 +565,36 @@ fn handle_table_key(key: crossterm::event::KeyEvent, app: &mut App, db_path: &st
                 app.focus = Focus::Input;
             }
         }
-        KeyCode::Char('r') => {
+        (Some(Action::Select), _) => {
             if let (Some(table), Some(row)) = (&app.selected_table, app.selected_row) {
                 let filtered = app.get_filtered_records(table);
                 if row < filtered.len() {
                     let record = &filtered[row];
-                    let pretty_hex = record.raw_data.iter().map(|byte| format!("{:02x}", byte)).collect::<Vec<String>>().join(" ");
-                    app.show_raw_data = Some(format!("raw data for {}:\n{}", record.key, pretty_hex));
+                    app.open_text_popup("decoded value", format!("{}:\n{}", record.key, record.pretty_value()));
+                }
+            }
+        }
+        (Some(Action::Copy), _) if app.selected_record().is_some() => {
+            app.copy_menu = true;
+        }
+        (Some(Action::Search), _) => app.focus = Focus::Input,
+        (Some(Action::Columns), _) => app.open_column_chooser(),
+        (Some(Action::DetailPanel), _) => app.detail_panel = !app.detail_panel,
+        (_, KeyCode::Char('<')) if app.detail_panel => app.resize_detail_panel(5),
+        (_, KeyCode::Char('>')) if app.detail_panel => app.resize_detail_panel(-5),
+        (None, KeyCode::Up) if app.detail_panel && shift => app.scroll_detail_panel(-1),
+        (None, KeyCode::Down) if app.detail_panel && shift => app.scroll_detail_panel(1),
+        (Some(Action::Mark), _) => app.toggle_mark(),
+        (Some(Action::Compare), _) => app.open_record_diff(),
+        (Some(Action::HexDump), _) => {
+            if let (Some(table), Some(row)) = (&app.selected_table, app.selected_row) {
+                let filtered = app.get_filtered_records(table);
+                if row < filtered.len() {
+                    app.open_hex_view(&filtered[row]);
                 }
             }
         }
-        KeyCode::PageDown => {
+        (Some(Action::PageDown), _) => {
             if let Some(table) = &app.selected_table {
                 let height = app.rows_per_page.max(1) as u16;
                 let total_pages = app.get_total_pages(table, height);
@@ -191,7 +606,7 @@ fn handle_table_key(key: crossterm::event::KeyEvent, app: &mut App, db_path: &st
                 }
             }
         },
-        KeyCode::PageUp => {
+        (Some(Action::PageUp), _) => {
             if app.current_page > 0 {
                 app.current_page -= 1;
                 let start_idx = app.current_page * app.rows_per_page.max(1);
@@ -201,45 +616,54 @@ fn handle_table_key(key: crossterm::event::KeyEvent, app: &mut App, db_path: &st
                 app.selected_row = Some(sel.max(start_idx));
             }
         },
-        KeyCode::Char('d') => {
-            if let (Some(table), Some(row)) = (app.selected_table.as_ref(), app.selected_row) {
-                let filtered = app.get_filtered_records(table);
+        (Some(Action::Delete), _) => {
+            if !app.writable() {
+                app.status_message = Some("deleting needs --writable".to_string());
+                return;
+            }
+            if let (Some(table), Some(row)) = (app.selected_table.clone(), app.selected_row) {
+                let filtered = app.get_filtered_records(&table);
                 if row < filtered.len() {
                     let key_to_remove = filtered[row].key.clone();
-                    app.show_raw_data = Some(format!("Attempting to delete key: {}", key_to_remove));
+                    app.open_text_popup("message", format!("Attempting to delete key: {}", key_to_remove));
 
-                    let mut opts = Options::default();
-                    opts.create_if_missing(false);
-                    match rocksdb::DB::open(&opts, db_path) {
+                    match crate::data::open_writable(&app.db_path, &app.overrides) {
                         Ok(db) => {
                             match db.delete(key_to_remove.as_bytes()) {
                                 Ok(_) => {
-                                    app.data_manager.delete_record(table, &key_to_remove);
-                                    app.show_raw_data = Some(format!("Successfully deleted key: {}", key_to_remove));
+                                    app.data_manager.delete_record(&table, &key_to_remove);
+                                    app.open_text_popup("message", format!("Successfully deleted key: {}", key_to_remove));
 
-                                    if app.data_manager.get_records().get(table).map_or(true, |r| r.is_empty()) {
+                                    if app.data_manager.get_records().get(&table).map_or(true, |r| r.is_empty()) {
                                         app.selected_table = None;
                                         app.selected_row = None;
                                     } else {
-                                        let max_row = app.data_manager.get_records().get(table).unwrap().len().saturating_sub(1);
+                                        let max_row = app.data_manager.get_records().get(&table).unwrap().len().saturating_sub(1);
                                         app.selected_row = Some(row.min(max_row));
                                     }
                                 }
                                 Err(e) => {
-                                    app.show_raw_data = Some(format!("Error deleting key {}: {}", key_to_remove, e));
+                                    app.open_text_popup("message", format!("Error deleting key {}: {}", key_to_remove, e));
                                 }
                             }
                         }
                         Err(e) => {
-                            app.show_raw_data = Some(format!("Error opening DB: {}", e));
+                            app.open_text_popup("message", format!("Error opening DB: {}", e));
                         }
                     }
                     thread::sleep(Duration::from_millis(1000));
                 }
             }
         }
-        KeyCode::Up => handle_navigation_up(app),
-        KeyCode::Down => handle_navigation_down(app),
+        (Some(Action::Left), _) => app.scroll_columns(-1),
+        (Some(Action::Right), _) => app.scroll_columns(1),
+        (Some(Action::Top), _) => {
+            app.col_offset = 0;
+            app.select_row(0);
+        }
+        (Some(Action::Bottom), _) => app.select_row(usize::MAX),
+        (Some(Action::Up), _) => handle_navigation_up(app),
+        (Some(Action::Down), _) => handle_navigation_down(app),
         _ => {}
     }
 }
@@ -306,6 +730,29 @@ fn handle_navigation_down(app: &mut App) {
 }
 
 fn handle_mouse_event(mouse_event: crossterm::event::MouseEvent, app: &mut App, chunks: &[ratatui::layout::Rect]) {
+    if matches!(app.view, View::Charts | View::Sst | View::Wal | View::Diff | View::Backups) {
+        return;
+    }
+
+    if app.view == View::Stats {
+        match mouse_event.kind {
+            MouseEventKind::ScrollUp => app.stats_scroll = app.stats_scroll.saturating_sub(3),
+            MouseEventKind::ScrollDown => app.stats_scroll = app.stats_scroll.saturating_add(3),
+            _ => {}
+        }
+        return;
+    }
+
+    // The detail panel scrolls under the wheel and ignores clicks
+    if app.detail_x > 0 && chunks.len() > 2 && mouse_event.column >= app.detail_x && mouse_event.row >= chunks[2].top() && mouse_event.row < chunks[2].bottom() {
+        match mouse_event.kind {
+            MouseEventKind::ScrollUp => app.scroll_detail_panel(-3),
+            MouseEventKind::ScrollDown => app.scroll_detail_panel(3),
+            _ => {}
+        }
+        return;
+    }
+
     if mouse_event.kind == MouseEventKind::Down(MouseButton::Left) {
         if chunks.len() > 3 && mouse_event.row >= chunks[3].top() && mouse_event.row < chunks[3].bottom() {
             if let Some(table) = &app.selected_table {
@@ -349,23 +796,18 @@ fn handle_mouse_event(mouse_event: crossterm::event::MouseEvent, app: &mut App,
                     let header_y = chunks[2].y + 1;
                     if mouse_event.row == header_y {
                         let start_x = chunks[2].x + 1;
-                        let max_width = chunks[2].width.saturating_sub(2);
-                        let widths = app.calculate_column_widths(table, max_width);
+                        let layout = app.layout_columns(table, app.table_width);
+                        let headers = app.display_headers(table);
                         let mut current_x = start_x;
-                        for (i, &width) in widths.iter().enumerate() {
-                            if mouse_event.column >= current_x && mouse_event.column < current_x + width + 3 {
-                                if app.sort_column == Some(i) {
-                                    app.sort_ascending = !app.sort_ascending;
-                                } else {
-                                    app.sort_column = Some(i);
-                                    app.sort_ascending = true;
-                                }
+                        for (&i, &width) in layout.columns.iter().zip(layout.widths.iter()) {
+                            if mouse_event.column >= current_x && mouse_event.column < current_x + width + COLUMN_SPACING {
+                                app.toggle_sort(&headers[i], mouse_event.modifiers.contains(KeyModifiers::SHIFT));
                                 app.selected_row = Some(0);
                                 app.scroll_y = 0;
                                 app.current_page = 0;
                                 break;
                             }
-                            current_x += width + 3;
+                            current_x += width + COLUMN_SPACING;
                         }
                     } else {
                         let rows_per_page = app.rows_per_page.max(1);
@@ -379,9 +821,7 @@ fn handle_mouse_event(mouse_event: crossterm::event::MouseEvent, app: &mut App,
                                 let now = std::time::Instant::now();
                                 if let Some((last_time, last_table, last_row)) = &app.last_click {
                                     if now.duration_since(*last_time).as_millis() < 500 && *last_table == *table && *last_row == row_index {
-                                        let record = &filtered[row_index];
-                                        let pretty_hex = record.raw_data.iter().map(|byte| format!("{:02x}", byte)).collect::<Vec<String>>().join(" ");
-                                        app.show_raw_data = Some(format!("{}:\n{}", record.key, pretty_hex));
+                                        app.open_hex_view(&filtered[row_index]);
                                         app.last_click = None;
                                     } else {
                                         app.last_click = Some((now, table.clone(), row_index));
@@ -404,8 +844,8 @@ fn handle_mouse_event(mouse_event: crossterm::event::MouseEvent, app: &mut App,
                     app.selected_table = Some(types[app.table_select_index].clone());
                     app.selected_row = Some(0);
                     app.focus = Focus::Table;
-                    app.sort_column = None;
-                    app.sort_ascending = true;
+                    app.sort_keys.clear();
+                    app.col_offset = 0;
                 }
             }
     } else if app.focus == Focus::Table {
@@ -422,9 +862,7 @@ fn handle_mouse_event(mouse_event: crossterm::event::MouseEvent, app: &mut App,
                             let now = std::time::Instant::now();
                             if let Some((last_time, last_table, last_row)) = &app.last_click {
                                 if now.duration_since(*last_time).as_millis() < 500 && *last_table == *table && *last_row == row_index {
-                                    let record = &filtered[row_index];
-                                    let pretty_hex = record.raw_data.iter().map(|byte| format!("{:02x}", byte)).collect::<Vec<String>>().join(" ");
-                                    app.show_raw_data = Some(format!("Raw data for {}:\n{}", record.key, pretty_hex));
+                                    app.open_hex_view(&filtered[row_index]);
                                     app.last_click = None;
                                 } else {
                                     app.last_click = Some((now, table.clone(), row_index));
@@ -0,0 +1,197 @@
+pub const BYTES_PER_LINE: usize = 16;
+
+pub struct HexView {
+    pub title: String,
+    pub data: Vec<u8>,
+    pub scroll: usize,
+    pub cursor: usize,
+    pub anchor: Option<usize>,
+    pub page_lines: usize,
+    pub search_input: Option<String>,
+    pub search_pattern: Vec<u8>,
+    pub message: Option<String>,
+}
+
+impl HexView {
+    pub fn new(title: &str, data: &[u8]) -> Self {
+        Self {
+            title: title.to_string(),
+            data: data.to_vec(),
+            scroll: 0,
+            cursor: 0,
+            anchor: None,
+            page_lines: 16,
+            search_input: None,
+            search_pattern: Vec::new(),
+            message: None,
+        }
+    }
+
+    pub fn total_lines(&self) -> usize {
+        self.data.len().div_ceil(BYTES_PER_LINE).max(1)
+    }
+
+    /// Byte range covered by the selection, or just the cursor byte when nothing is selected.
+    pub fn selection(&self) -> (usize, usize) {
+        match self.anchor {
+            Some(anchor) => (anchor.min(self.cursor), anchor.max(self.cursor)),
+            None => (self.cursor, self.cursor),
+        }
+    }
+
+    pub fn is_selected(&self, offset: usize) -> bool {
+        let (start, end) = self.selection();
+        self.anchor.is_some() && offset >= start && offset <= end
+    }
+
+    pub fn is_match(&self, offset: usize) -> bool {
+        let len = self.search_pattern.len();
+        if len == 0 {
+            return false;
+        }
+        let from = offset.saturating_sub(len - 1);
+        (from..=offset).any(|start| self.data.get(start..start + len) == Some(self.search_pattern.as_slice()))
+    }
+
+    pub fn move_cursor(&mut self, delta: isize, extend: bool) {
+        if self.data.is_empty() {
+            return;
+        }
+        if extend && self.anchor.is_none() {
+            self.anchor = Some(self.cursor);
+        } else if !extend {
+            self.anchor = None;
+        }
+        let max = self.data.len() as isize - 1;
+        self.cursor = (self.cursor as isize + delta).clamp(0, max) as usize;
+        self.ensure_cursor_visible();
+    }
+
+    pub fn move_to(&mut self, offset: usize) {
+        self.cursor = offset.min(self.data.len().saturating_sub(1));
+        self.ensure_cursor_visible();
+    }
+
+    pub fn toggle_selection(&mut self) {
+        if self.data.is_empty() {
+            return;
+        }
+        self.anchor = if self.anchor.is_some() { None } else { Some(self.cursor) };
+    }
+
+    pub fn scroll_by(&mut self, delta: isize) {
+        let max = self.total_lines().saturating_sub(self.page_lines.max(1)) as isize;
+        self.scroll = (self.scroll as isize + delta).clamp(0, max.max(0)) as usize;
+    }
+
+    fn ensure_cursor_visible(&mut self) {
+        let line = self.cursor / BYTES_PER_LINE;
+        let page = self.page_lines.max(1);
+        if line < self.scroll {
+            self.scroll = line;
+        } else if line >= self.scroll + page {
+            self.scroll = line + 1 - page;
+        }
+    }
+
+    pub fn format_line(&self, line: usize) -> (String, Vec<Option<u8>>) {
+        let offset = line * BYTES_PER_LINE;
+        let bytes = (0..BYTES_PER_LINE).map(|i| self.data.get(offset + i).copied()).collect();
+        (format!("{:08x}", offset), bytes)
+    }
+
+    /// Runs the pattern typed into the search prompt and jumps to its first match after the cursor.
+    pub fn submit_search(&mut self) {
+        let Some(input) = self.search_input.take() else { return };
+        match parse_pattern(&input) {
+            Some(pattern) => {
+                self.search_pattern = pattern;
+                self.find(true);
+            }
+            None => self.message = Some(format!("invalid pattern: {}", input)),
+        }
+    }
+
+    pub fn find(&mut self, forward: bool) {
+        if self.search_pattern.is_empty() {
+            return;
+        }
+        let pattern = &self.search_pattern;
+        let positions: Vec<usize> = self.data.windows(pattern.len())
+            .enumerate()
+            .filter(|(_, w)| *w == pattern.as_slice())
+            .map(|(i, _)| i)
+            .collect();
+        let found = if forward {
+            positions.iter().find(|&&p| p > self.cursor).or(positions.first())
+        } else {
+            positions.iter().rev().find(|&&p| p < self.cursor).or(positions.last())
+        };
+        match found {
+            Some(&pos) => {
+                self.anchor = None;
+                self.move_to(pos);
+                self.message = Some(format!("{} match(es)", positions.len()));
+            }
+            None => self.message = Some("pattern not found".to_string()),
+        }
+    }
+
+    /// Selected bytes read as the common fixed-width and variable-length encodings.
+    /// Without a selection the bytes from the cursor onwards are used.
+    pub fn interpretations(&self) -> Vec<(String, String)> {
+        let (start, end) = self.selection();
+        let bytes: &[u8] = if self.anchor.is_some() {
+            &self.data[start..=end.min(self.data.len().saturating_sub(1))]
+        } else {
+            self.data.get(start..).unwrap_or(&[])
+        };
+
+        fn fixed<const N: usize>(bytes: &[u8]) -> Option<[u8; N]> {
+            bytes.get(..N).and_then(|b| b.try_into().ok())
+        }
+        fn pair<T: std::fmt::Display>(le: Option<T>, be: Option<T>) -> String {
+            match (le, be) {
+                (Some(le), Some(be)) => format!("le {:<22} be {}", le, be),
+                _ => "-".to_string(),
+            }
+        }
+
+        vec![
+            ("u16".to_string(), pair(fixed::<2>(bytes).map(u16::from_le_bytes), fixed::<2>(bytes).map(u16::from_be_bytes))),
+            ("u32".to_string(), pair(fixed::<4>(bytes).map(u32::from_le_bytes), fixed::<4>(bytes).map(u32::from_be_bytes))),
+            ("u64".to_string(), pair(fixed::<8>(bytes).map(u64::from_le_bytes), fixed::<8>(bytes).map(u64::from_be_bytes))),
+            ("f32".to_string(), pair(fixed::<4>(bytes).map(f32::from_le_bytes), fixed::<4>(bytes).map(f32::from_be_bytes))),
+            ("f64".to_string(), pair(fixed::<8>(bytes).map(f64::from_le_bytes), fixed::<8>(bytes).map(f64::from_be_bytes))),
+            ("varint".to_string(), match decode_varint(bytes) {
+                Some((value, len)) => format!("{} ({} bytes, zigzag {})", value, len, ((value >> 1) as i64) ^ -((value & 1) as i64)),
+                None => "-".to_string(),
+            }),
+        ]
+    }
+}
+
+/// Decodes an unsigned LEB128 varint, returning the value and the number of bytes consumed.
+pub fn decode_varint(bytes: &[u8]) -> Option<(u64, usize)> {
+    let mut value: u64 = 0;
+    for (i, &byte) in bytes.iter().enumerate().take(10) {
+        value |= ((byte & 0x7f) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
+
+/// Parses a search pattern: a quoted string is matched as text, anything else as hex bytes.
+fn parse_pattern(input: &str) -> Option<Vec<u8>> {
+    let trimmed = input.trim();
+    if trimmed.len() >= 2 && trimmed.starts_with('"') && trimmed.ends_with('"') {
+        return Some(trimmed.as_bytes()[1..trimmed.len() - 1].to_vec());
+    }
+    let hex: String = trimmed.chars().filter(|c| !c.is_whitespace()).collect();
+    if hex.is_empty() || !hex.len().is_multiple_of(2) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok()).collect()
+}
@@ -0,0 +1,406 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashMap;
+
+pub type KeyBinding = (KeyCode, KeyModifiers);
+
+/// Everything a key can be bound to. The handlers in `events.rs` act on these, so a key does
+/// only what the keymap binds it to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
+    Select,
+    Back,
+    NextFocus,
+    PrevFocus,
+    Search,
+    NextMatch,
+    PrevMatch,
+    HexDump,
+    Copy,
+    Columns,
+    Mark,
+    Compare,
+    Delete,
+    DetailPanel,
+    Refresh,
+    OpenDatabase,
+    NewTab,
+    CloseTab,
+    PrevTab,
+    NextTab,
+    Stats,
+    Charts,
+    SstFiles,
+    Wal,
+    Freeze,
+    Diff,
+    Checkpoint,
+    Backups,
+    Admin,
+    Quit,
+}
+
+impl Action {
+    pub const ALL: [Action; 38] = [
+        Action::Up,
+        Action::Down,
+        Action::Left,
+        Action::Right,
+        Action::PageUp,
+        Action::PageDown,
+        Action::Top,
+        Action::Bottom,
+        Action::Select,
+        Action::Back,
+        Action::NextFocus,
+        Action::PrevFocus,
+        Action::Search,
+        Action::NextMatch,
+        Action::PrevMatch,
+        Action::HexDump,
+        Action::Copy,
+        Action::Columns,
+        Action::Mark,
+        Action::Compare,
+        Action::Delete,
+        Action::DetailPanel,
+        Action::Refresh,
+        Action::OpenDatabase,
+        Action::NewTab,
+        Action::CloseTab,
+        Action::PrevTab,
+        Action::NextTab,
+        Action::Stats,
+        Action::Charts,
+        Action::SstFiles,
+        Action::Wal,
+        Action::Freeze,
+        Action::Diff,
+        Action::Checkpoint,
+        Action::Backups,
+        Action::Admin,
+        Action::Quit,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::Left => "left",
+            Action::Right => "right",
+            Action::PageUp => "page-up",
+            Action::PageDown => "page-down",
+            Action::Top => "top",
+            Action::Bottom => "bottom",
+            Action::Select => "select",
+            Action::Back => "back",
+            Action::NextFocus => "next-focus",
+            Action::PrevFocus => "prev-focus",
+            Action::Search => "search",
+            Action::NextMatch => "next-match",
+            Action::PrevMatch => "prev-match",
+            Action::HexDump => "hex-dump",
+            Action::Copy => "copy",
+            Action::Columns => "columns",
+            Action::Mark => "mark",
+            Action::Compare => "compare",
+            Action::Delete => "delete",
+            Action::DetailPanel => "detail-panel",
+            Action::Refresh => "refresh",
+            Action::OpenDatabase => "open-database",
+            Action::NewTab => "new-tab",
+            Action::CloseTab => "close-tab",
+            Action::PrevTab => "prev-tab",
+            Action::NextTab => "next-tab",
+            Action::Stats => "stats",
+            Action::Charts => "charts",
+            Action::SstFiles => "sst-files",
+            Action::Wal => "wal",
+            Action::Freeze => "freeze",
+            Action::Diff => "diff",
+            Action::Checkpoint => "checkpoint",
+            Action::Backups => "backups",
+            Action::Admin => "admin",
+            Action::Quit => "quit",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.name() == name)
+    }
+}
+
+/// A key press after the keymap: the action it is bound to, if any, and the key itself, which
+/// text fields and the shortcuts of single screens (such as `n` on the backups screen) read.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Input {
+    pub action: Option<Action>,
+    pub key: KeyEvent,
+}
+
+/// The bindings every preset starts from.
+const DEFAULT: [(&str, &str); 39] = [
+    ("up", "up"),
+    ("down", "down"),
+    ("left", "left"),
+    ("right", "right"),
+    ("pageup", "page-up"),
+    ("pagedown", "page-down"),
+    ("home", "top"),
+    ("end", "bottom"),
+    ("enter", "select"),
+    ("esc", "back"),
+    ("tab", "next-focus"),
+    ("shift+tab", "prev-focus"),
+    ("/", "search"),
+    ("n", "next-match"),
+    ("N", "prev-match"),
+    ("r", "hex-dump"),
+    ("c", "copy"),
+    ("o", "columns"),
+    ("m", "mark"),
+    ("=", "compare"),
+    ("d", "delete"),
+    ("p", "detail-panel"),
+    ("ctrl+r", "refresh"),
+    ("ctrl+o", "open-database"),
+    ("ctrl+t", "new-tab"),
+    ("ctrl+w", "close-tab"),
+    ("alt+left", "prev-tab"),
+    ("alt+right", "next-tab"),
+    ("f2", "stats"),
+    ("f3", "charts"),
+    ("f4", "sst-files"),
+    ("f5", "wal"),
+    ("f6", "freeze"),
+    ("f7", "diff"),
+    ("f8", "checkpoint"),
+    ("f9", "backups"),
+    ("f10", "admin"),
+    ("ctrl+c", "quit"),
+    ("q", "quit"),
+];
+
+pub const PRESETS: [&str; 3] = ["default", "vim", "emacs"];
+
+const VIM: [(&str, &str); 8] = [
+    ("h", "left"),
+    ("j", "down"),
+    ("k", "up"),
+    ("l", "right"),
+    ("g g", "top"),
+    ("G", "bottom"),
+    ("ctrl+d", "page-down"),
+    ("ctrl+u", "page-up"),
+];
+
+const EMACS: [(&str, &str); 10] = [
+    ("ctrl+p", "up"),
+    ("ctrl+n", "down"),
+    ("ctrl+b", "left"),
+    ("ctrl+f", "right"),
+    ("alt+v", "page-up"),
+    ("ctrl+v", "page-down"),
+    ("alt+<", "top"),
+    ("alt+>", "bottom"),
+    ("ctrl+s", "search"),
+    ("ctrl+g", "back"),
+];
+
+/// Drops Shift from character keys so they compare like the bindings from `parse_key`.
+pub fn normalize_key(code: KeyCode, mut modifiers: KeyModifiers) -> KeyBinding {
+    if let KeyCode::Char(_) = code {
+        modifiers.remove(KeyModifiers::SHIFT);
+    }
+    (code, modifiers)
+}
+
+/// Parses keys such as `j`, `ctrl+n`, `shift+tab`, `pagedown` or `f5`.
+pub fn parse_key(s: &str) -> Option<KeyBinding> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut rest = s.trim();
+    while let Some((modifier, tail)) = rest.split_once('+').filter(|(_, tail)| !tail.is_empty()) {
+        modifiers |= match modifier.to_ascii_lowercase().as_str() {
+            "ctrl" => KeyModifiers::CONTROL,
+            "alt" | "meta" => KeyModifiers::ALT,
+            "shift" => KeyModifiers::SHIFT,
+            _ => return None,
+        };
+        rest = tail;
+    }
+    let code = match rest.to_ascii_lowercase().as_str() {
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "pageup" | "pgup" => KeyCode::PageUp,
+        "pagedown" | "pgdn" => KeyCode::PageDown,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "enter" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "tab" if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+        "tab" => KeyCode::Tab,
+        "backspace" => KeyCode::Backspace,
+        "delete" | "del" => KeyCode::Delete,
+        "space" => KeyCode::Char(' '),
+        f if f.len() > 1 && f.starts_with('f') => KeyCode::F(f[1..].parse().ok()?),
+        _ => {
+            let mut chars = rest.chars();
+            let c = chars.next()?;
+            if chars.next().is_some() {
+                return None;
+            }
+            // Shifted characters are matched by the character alone, as terminals differ in reporting Shift
+            modifiers.remove(KeyModifiers::SHIFT);
+            KeyCode::Char(c)
+        }
+    };
+    Some((code, modifiers))
+}
+
+/// Parses a key sequence: keys separated by spaces (`g g`), or a run of plain characters (`gg`).
+fn parse_sequence(s: &str) -> Option<Vec<KeyBinding>> {
+    let keys: Option<Vec<KeyBinding>> = s.split_whitespace().map(parse_key).collect();
+    match keys {
+        Some(keys) if !keys.is_empty() => Some(keys),
+        _ if !s.contains(['+', ' ']) => s.chars().map(|c| parse_key(&c.to_string())).collect::<Option<Vec<_>>>().filter(|keys| keys.len() > 1),
+        _ => None,
+    }
+}
+
+/// Key sequences and the actions they are bound to.
+#[derive(Clone, Debug)]
+pub struct Keymap {
+    bindings: HashMap<Vec<KeyBinding>, Action>,
+    /// The keys typed so far of a sequence that is not complete yet.
+    pending: Vec<KeyBinding>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let mut keymap = Self { bindings: HashMap::new(), pending: Vec::new() };
+        for (keys, action) in DEFAULT {
+            keymap.bind(keys, action).expect("default bindings parse");
+        }
+        keymap
+    }
+}
+
+impl Keymap {
+    /// The default bindings plus those of the preset.
+    pub fn preset(name: &str) -> Option<Self> {
+        let bindings: &[(&str, &str)] = match name {
+            "default" => &[],
+            "vim" => &VIM,
+            "emacs" => &EMACS,
+            _ => return None,
+        };
+        let mut keymap = Self::default();
+        for (keys, action) in bindings {
+            keymap.bind(keys, action).ok()?;
+        }
+        Some(keymap)
+    }
+
+    /// Binds a key sequence to an action, to the action of another key, or to `none`, which
+    /// takes the sequence's binding away.
+    pub fn bind(&mut self, keys: &str, target: &str) -> Result<(), String> {
+        let sequence = parse_sequence(keys).ok_or_else(|| format!("unknown key {}", keys))?;
+        if target == "none" {
+            self.bindings.remove(&sequence);
+            return Ok(());
+        }
+        let action = Action::from_name(target)
+            .or_else(|| parse_key(target).and_then(|key| self.bindings.get(&vec![key]).copied()))
+            .ok_or_else(|| format!("{} must map to an action such as \"down\", a key bound to one, or \"none\"", keys))?;
+        self.bindings.insert(sequence, action);
+        Ok(())
+    }
+
+    /// What a pressed key comes to: nothing while it starts a longer sequence, otherwise the
+    /// key with its action. When a started sequence does not go on as bound, its first key is
+    /// passed on by itself and the keys after it are resolved again.
+    pub fn resolve(&mut self, key: KeyBinding) -> Vec<(Option<Action>, KeyBinding)> {
+        self.pending.push(key);
+        if let Some(&action) = self.bindings.get(&self.pending) {
+            self.pending.clear();
+            return vec![(Some(action), key)];
+        }
+        if self.bindings.keys().any(|sequence| sequence.len() > self.pending.len() && sequence.starts_with(&self.pending)) {
+            return Vec::new();
+        }
+        let pending = std::mem::take(&mut self.pending);
+        let first = pending[0];
+        let mut resolved = vec![(self.bindings.get(&vec![first]).copied(), first)];
+        for &key in &pending[1..] {
+            resolved.extend(self.resolve(key));
+        }
+        resolved
+    }
+
+    pub fn reset(&mut self) {
+        self.pending.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(c: char) -> KeyBinding {
+        (KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn parse_sequence_reads_spaced_and_run_together_keys() {
+        assert_eq!(parse_sequence("g g"), Some(vec![key('g'), key('g')]));
+        assert_eq!(parse_sequence("gg"), Some(vec![key('g'), key('g')]));
+        assert_eq!(parse_sequence("ctrl+x k"), Some(vec![(KeyCode::Char('x'), KeyModifiers::CONTROL), key('k')]));
+        assert_eq!(parse_sequence("G"), Some(vec![key('G')]));
+        assert_eq!(parse_sequence("shift+tab"), Some(vec![(KeyCode::BackTab, KeyModifiers::SHIFT)]));
+        assert_eq!(parse_sequence("ctrl+"), None);
+        assert_eq!(parse_sequence(""), None);
+    }
+
+    #[test]
+    fn resolve_finishes_sequences_and_waits_while_one_is_started() {
+        let mut keymap = Keymap::preset("vim").unwrap();
+        assert_eq!(keymap.resolve(key('j')), vec![(Some(Action::Down), key('j'))]);
+        assert_eq!(keymap.resolve(key('g')), vec![]);
+        assert_eq!(keymap.resolve(key('g')), vec![(Some(Action::Top), key('g'))]);
+        assert_eq!(keymap.resolve(key('x')), vec![(None, key('x'))]);
+    }
+
+    #[test]
+    fn resolve_passes_on_the_keys_of_a_sequence_that_breaks_off() {
+        let mut keymap = Keymap::preset("vim").unwrap();
+        assert_eq!(keymap.resolve(key('g')), vec![]);
+        assert_eq!(keymap.resolve(key('j')), vec![(None, key('g')), (Some(Action::Down), key('j'))]);
+        // The key that broke the sequence off can start a new one
+        assert_eq!(keymap.resolve(key('g')), vec![]);
+        assert_eq!(keymap.resolve(key('d')), vec![(None, key('g')), (Some(Action::Delete), key('d'))]);
+    }
+
+    #[test]
+    fn rebinding_a_key_replaces_its_action() {
+        let mut keymap = Keymap::default();
+        keymap.bind("d", "mark").unwrap();
+        keymap.bind("x", "delete").unwrap();
+        keymap.bind("q", "none").unwrap();
+        keymap.bind("ctrl+x", "pagedown").unwrap();
+        assert_eq!(keymap.resolve(key('d')), vec![(Some(Action::Mark), key('d'))]);
+        assert_eq!(keymap.resolve(key('x')), vec![(Some(Action::Delete), key('x'))]);
+        assert_eq!(keymap.resolve(key('q')), vec![(None, key('q'))]);
+        let ctrl_x = (KeyCode::Char('x'), KeyModifiers::CONTROL);
+        assert_eq!(keymap.resolve(ctrl_x), vec![(Some(Action::PageDown), ctrl_x)]);
+        assert!(keymap.bind("y", "nowhere").is_err());
+    }
+}
@@ -0,0 +1,211 @@
+use crate::timestamps::TimeFormat;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColumnConfig {
+    pub name: String,
+    pub visible: bool,
+    pub width: Option<u16>,
+    /// How the column's values are read as timestamps.
+    pub time: TimeFormat,
+}
+
+/// Column order, visibility and fixed widths for one record type. The key column is not
+/// part of the layout; it is always shown first.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TableLayout {
+    pub columns: Vec<ColumnConfig>,
+}
+
+impl TableLayout {
+    /// Merges the saved layout with the fields discovered in the data. Saved columns keep
+    /// their position; newly discovered ones are appended and visible.
+    pub fn resolve(&self, discovered: &[String]) -> Vec<ColumnConfig> {
+        let mut columns: Vec<ColumnConfig> = self.columns.iter()
+            .filter(|c| discovered.contains(&c.name))
+            .cloned()
+            .collect();
+        for name in discovered {
+            if !columns.iter().any(|c| &c.name == name) {
+                columns.push(ColumnConfig { name: name.clone(), visible: true, width: None, time: TimeFormat::Auto });
+            }
+        }
+        columns
+    }
+
+    /// The layout to save after editing the resolved `columns`. Saved columns that are missing
+    /// from the current data are kept at their old positions, so they return when the data has them.
+    pub fn updated(&self, columns: Vec<ColumnConfig>) -> TableLayout {
+        let mut updated = columns;
+        for (i, column) in self.columns.iter().enumerate() {
+            if !updated.iter().any(|c| c.name == column.name) {
+                updated.insert(i.min(updated.len()), column.clone());
+            }
+        }
+        TableLayout { columns: updated }
+    }
+
+    fn to_json(&self) -> Value {
+        let columns: Vec<Value> = self.columns.iter().map(|c| {
+            let mut column = json!({ "name": c.name, "visible": c.visible });
+            if let Some(width) = c.width {
+                column["width"] = json!(width);
+            }
+            if c.time != TimeFormat::Auto {
+                column["time"] = json!(c.time.label());
+            }
+            column
+        }).collect();
+        json!({ "columns": columns })
+    }
+
+    /// Reads a layout saved by `to_json`. Columns given as a bare name are visible with an automatic width.
+    pub fn from_json(value: &Value) -> Self {
+        let columns = value.get("columns").and_then(Value::as_array).map(|columns| {
+            columns.iter().filter_map(|c| {
+                if let Some(name) = c.as_str() {
+                    return Some(ColumnConfig { name: name.to_string(), visible: true, width: None, time: TimeFormat::Auto });
+                }
+                Some(ColumnConfig {
+                    name: c.get("name")?.as_str()?.to_string(),
+                    visible: c.get("visible").and_then(Value::as_bool).unwrap_or(true),
+                    width: c.get("width").and_then(Value::as_u64).map(|w| w.min(u16::MAX as u64) as u16),
+                    time: c.get("time").and_then(Value::as_str).and_then(TimeFormat::from_label).unwrap_or_default(),
+                })
+            }).collect()
+        }).unwrap_or_default();
+        Self { columns }
+    }
+}
+
+/// Per record type table layouts, persisted as JSON in the config directory.
+pub struct LayoutStore {
+    path: Option<PathBuf>,
+    pub layouts: HashMap<String, TableLayout>,
+    /// Layouts from the config file, used for record types without a saved layout.
+    pub defaults: HashMap<String, TableLayout>,
+}
+
+impl LayoutStore {
+    pub fn load() -> Self {
+        let path = crate::config::config_dir().map(|dir| dir.join("layouts.json"));
+        let layouts = path.as_ref()
+            .and_then(|p| std::fs::read(p).ok())
+            .and_then(|bytes| serde_json::from_slice::<Value>(&bytes).ok())
+            .and_then(|value| value.as_object().cloned())
+            .map(|map| map.iter().map(|(k, v)| (k.clone(), TableLayout::from_json(v))).collect())
+            .unwrap_or_default();
+        Self { path, layouts, defaults: HashMap::new() }
+    }
+
+    pub fn get(&self, record_type: &str) -> Option<&TableLayout> {
+        self.layouts.get(record_type).or_else(|| self.defaults.get(record_type))
+    }
+
+    pub fn set(&mut self, record_type: &str, layout: TableLayout) -> anyhow::Result<()> {
+        self.layouts.insert(record_type.to_string(), layout);
+        self.save()
+    }
+
+    fn save(&self) -> anyhow::Result<()> {
+        let Some(path) = &self.path else {
+            anyhow::bail!("no config directory (set $XDG_CONFIG_HOME or $HOME)");
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let map: Map<String, Value> = self.layouts.iter().map(|(k, v)| (k.clone(), v.to_json())).collect();
+        std::fs::write(path, serde_json::to_vec_pretty(&Value::Object(map))?)?;
+        Ok(())
+    }
+}
+
+/// State of the column chooser popup while it is open.
+pub struct ColumnChooser {
+    pub record_type: String,
+    pub columns: Vec<ColumnConfig>,
+    pub index: usize,
+    pub width_input: Option<String>,
+}
+
+impl ColumnChooser {
+    pub fn new(record_type: &str, columns: Vec<ColumnConfig>) -> Self {
+        Self { record_type: record_type.to_string(), columns, index: 0, width_input: None }
+    }
+
+    pub fn select(&mut self, delta: isize) {
+        let max = self.columns.len().saturating_sub(1) as isize;
+        self.index = (self.index as isize + delta).clamp(0, max) as usize;
+    }
+
+    pub fn move_selected(&mut self, delta: isize) {
+        let target = self.index as isize + delta;
+        if target >= 0 && (target as usize) < self.columns.len() {
+            self.columns.swap(self.index, target as usize);
+            self.index = target as usize;
+        }
+    }
+
+    pub fn toggle_selected(&mut self) {
+        if let Some(column) = self.columns.get_mut(self.index) {
+            column.visible = !column.visible;
+        }
+    }
+
+    pub fn cycle_time_format(&mut self) {
+        if let Some(column) = self.columns.get_mut(self.index) {
+            column.time = column.time.next();
+        }
+    }
+
+    /// Applies the typed width to the selected column; an empty input restores automatic sizing.
+    pub fn submit_width(&mut self) {
+        let Some(input) = self.width_input.take() else { return };
+        if let Some(column) = self.columns.get_mut(self.index) {
+            column.width = input.trim().parse::<u16>().ok().filter(|w| *w > 0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(name: &str, visible: bool) -> ColumnConfig {
+        ColumnConfig { name: name.to_string(), visible, width: None, time: TimeFormat::Auto }
+    }
+
+    fn names(columns: &[ColumnConfig]) -> Vec<&str> {
+        columns.iter().map(|c| c.name.as_str()).collect()
+    }
+
+    #[test]
+    fn resolve_keeps_saved_order_and_appends_new_columns() {
+        let layout = TableLayout { columns: vec![column("b", true), column("gone", true), column("a", false)] };
+        let discovered = ["a".to_string(), "b".to_string(), "c".to_string()];
+        let resolved = layout.resolve(&discovered);
+        assert_eq!(names(&resolved), ["b", "a", "c"]);
+        assert!(!resolved[1].visible);
+        assert!(resolved[2].visible);
+    }
+
+    #[test]
+    fn updated_keeps_columns_missing_from_the_data() {
+        let layout = TableLayout { columns: vec![column("b", true), column("gone", false), column("a", true)] };
+        let mut edited = layout.resolve(&["a".to_string(), "b".to_string()]);
+        edited.swap(0, 1);
+        let updated = layout.updated(edited);
+        assert_eq!(names(&updated.columns), ["a", "gone", "b"]);
+        assert!(!updated.columns[1].visible);
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let mut layout = TableLayout { columns: vec![column("a", true), column("b", false)] };
+        layout.columns[0].width = Some(12);
+        layout.columns[1].time = TimeFormat::Millis;
+        assert_eq!(TableLayout::from_json(&layout.to_json()), layout);
+    }
+}
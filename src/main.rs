@@ -1,14 +1,29 @@
+mod admin;
+mod analytics;
 mod app;
+mod backup;
+mod clipboard;
+mod config;
 mod data;
+mod diff;
 mod events;
+mod hexdump;
+mod keymap;
+mod layout;
 mod models;
+mod options;
+mod profiles;
+mod sst;
+mod tabs;
+mod timestamps;
 mod ui;
+mod wal;
 
-use crate::app::{App, Focus};
-use clap::Parser;
+use crate::app::{App, Focus, View};
+use clap::{Parser, Subcommand};
 use crossterm::{
     cursor::EnableBlinking,
-    event,
+    event::{self, Event},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, Clear, ClearType},
 };
@@ -18,14 +33,201 @@ use std::{io, time::Duration};
 #[derive(Parser)]
 #[command(name = "rocksdb-viewer")]
 #[command(about = "A general RocksDB browser with TUI")]
+#[command(subcommand_negates_reqs = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Database directory; defaults to db_path from the selected profile or the config file
     #[arg(short, long)]
-    db_path: String,
+    db_path: Option<String>,
+
+    /// Use the settings of a [profiles.NAME] section of the config file
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    /// Browse a single .sst file instead of a database directory
+    #[arg(long, conflicts_with = "db_path")]
+    sst: Option<std::path::PathBuf>,
+
+    /// Ordering for a comparator the OPTIONS file names but the viewer does not know: bytewise, reverse or u64
+    #[arg(long, global = true)]
+    comparator: Option<String>,
+
+    /// Merge operator to resolve merged values with: uint64add, counter, append, max or put
+    #[arg(long, global = true)]
+    merge_operator: Option<String>,
+
+    /// Treat the database as written through DBWithTTL with this TTL in seconds (detected from the OPTIONS file otherwise)
+    #[arg(long, global = true)]
+    ttl: Option<i64>,
+
+    /// Time zone for timestamps: local (default), utc, an offset like +05:30 or a name like Europe/Berlin
+    #[arg(long, global = true)]
+    tz: Option<String>,
+
+    /// How to open the database: read-only, secondary or writable; wins over the profile and the config file
+    #[arg(long, global = true, value_parser = options::OpenMode::parse)]
+    mode: Option<options::OpenMode>,
+
+    /// Short for --mode writable, which allows maintenance operations from the admin menu, deletes and backups
+    #[arg(long, global = true, conflicts_with = "mode")]
+    writable: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Compare two databases key by key
+    Diff {
+        #[arg(long)]
+        left: String,
+
+        #[arg(long)]
+        right: String,
+
+        /// Show the differences in a split TUI view instead of printing them
+        #[arg(long)]
+        tui: bool,
+    },
+    /// Create a checkpoint of a database in a new directory
+    Checkpoint {
+        #[arg(short, long)]
+        db_path: String,
+
+        /// Directory to create; defaults to a timestamped sibling of the database
+        #[arg(long)]
+        to: Option<String>,
+    },
+    /// Create, list, verify and restore backups
+    Backup {
+        #[command(subcommand)]
+        command: BackupCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum BackupCommand {
+    /// Flush the database and add a backup
+    Create {
+        #[arg(short, long)]
+        db_path: String,
+
+        #[arg(long)]
+        backup_dir: String,
+    },
+    /// List the backups in a backup directory
+    List {
+        #[arg(long)]
+        backup_dir: String,
+    },
+    /// Check that the files of a backup exist with the expected sizes
+    Verify {
+        #[arg(long)]
+        backup_dir: String,
+
+        #[arg(long)]
+        id: u32,
+    },
+    /// Restore a backup into a new directory
+    Restore {
+        #[arg(long)]
+        backup_dir: String,
+
+        #[arg(long)]
+        id: u32,
+
+        #[arg(long)]
+        to: String,
+    },
+}
+
+fn run_backup_command(command: &Command, overrides: &options::Overrides) -> anyhow::Result<()> {
+    match command {
+        Command::Checkpoint { db_path, to } => {
+            let target = to.clone().unwrap_or_else(|| backup::default_checkpoint_dir(db_path));
+            backup::create_checkpoint(db_path, &target, overrides).map_err(anyhow::Error::msg)?;
+            println!("checkpoint created in {}", target);
+        }
+        Command::Backup { command: BackupCommand::Create { db_path, backup_dir } } => {
+            let id = backup::create_backup(db_path, backup_dir, overrides).map_err(anyhow::Error::msg)?;
+            println!("backup {} created in {}", id, backup_dir);
+        }
+        Command::Backup { command: BackupCommand::List { backup_dir } } => {
+            println!("{:>6}  {:<19}  {:>12}  {:>6}", "id", "created", "size", "files");
+            for info in backup::list_backups(backup_dir).map_err(anyhow::Error::msg)? {
+                println!("{:>6}  {:<19}  {:>12}  {:>6}", info.backup_id, backup::format_timestamp(info.timestamp), info.size, info.num_files);
+            }
+        }
+        Command::Backup { command: BackupCommand::Verify { backup_dir, id } } => {
+            backup::verify_backup(backup_dir, *id).map_err(anyhow::Error::msg)?;
+            println!("backup {} is intact", id);
+        }
+        Command::Backup { command: BackupCommand::Restore { backup_dir, id, to } } => {
+            backup::restore_backup(backup_dir, *id, to).map_err(anyhow::Error::msg)?;
+            println!("backup {} restored into {}", id, to);
+        }
+        Command::Diff { .. } => {}
+    }
+    Ok(())
 }
 
 fn main() -> anyhow::Result<()> {
     let args = Args::parse();
-    let app = App::new(&args.db_path);
+    let config = config::Config::load().map_err(anyhow::Error::msg)?;
+    let profile = match &args.profile {
+        Some(name) => Some(config.profiles.get(name).ok_or_else(|| anyhow::anyhow!("no profile {} in the config file", name))?),
+        None => None,
+    };
+    // Command line flags win over the profile, which wins over [defaults]
+    let settings = config::Settings {
+        db_path: args.db_path.clone(),
+        tz: args.tz.clone(),
+        comparator: args.comparator.clone(),
+        merge_operator: args.merge_operator.clone(),
+        ttl: args.ttl,
+        mode: args.mode.or(args.writable.then_some(options::OpenMode::Writable)),
+    }.or(&profile.map(|p| p.settings.clone()).unwrap_or_default()).or(&config.defaults);
+    let overrides = settings.overrides();
+    let rule_set = config.record_rules(profile);
+    let zone = settings.tz.as_deref().map(timestamps::parse_zone).transpose().map_err(anyhow::Error::msg)?.unwrap_or_default();
+    let diff = match &args.command {
+        Some(Command::Diff { left, right, tui }) => {
+            let diff = diff::diff_databases(left, right, &overrides).map_err(anyhow::Error::msg)?;
+            if !tui {
+                diff::print_report(&diff, &rule_set);
+                if !diff.is_empty() {
+                    std::process::exit(1);
+                }
+                return Ok(());
+            }
+            Some(diff::DiffState::new(left, right, diff, rule_set.clone()))
+        }
+        Some(command) => return run_backup_command(command, &overrides),
+        None => None,
+    };
+    let staged_sst = args.sst.as_deref().map(|path| sst::stage_sst_file(path, &overrides)).transpose()?;
+    let db_path = match (&args.db_path, &staged_sst, &diff, &settings.db_path) {
+        (Some(path), _, _, _) => Some(path.clone()),
+        (None, Some(staged), _, _) => Some(staged.path().to_string_lossy().to_string()),
+        (None, None, Some(diff), _) => Some(diff.left_path.clone()),
+        (None, None, None, Some(path)) => Some(path.clone()),
+        (None, None, None, None) => None,
+    };
+    let mut app = App::new(db_path.as_deref().unwrap_or_default(), overrides, rule_set);
+    app.zone = zone;
+    app.theme = config.theme.clone();
+    app.keymap = config.keys.clone();
+    app.layouts.defaults = config.layouts.clone();
+    app.profile = args.profile.clone();
+    app.config = config;
+    if db_path.is_none() {
+        // Without a database to open, start on the picker
+        app.profile_picker = Some(profiles::ProfilePicker::new(&app.config, None, true));
+    }
+    if diff.is_some() {
+        app.diff = diff;
+        app.set_view(View::Diff);
+    }
 
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -33,7 +235,7 @@ fn main() -> anyhow::Result<()> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let res = run_app(&mut terminal, app, &args.db_path);
+    let res = run_app(&mut terminal, tabs::Tabs::new(app));
 
     execute!(terminal.backend_mut(), Clear(ClearType::All))?;
     execute!(terminal.backend_mut(), crossterm::cursor::MoveTo(0, 0))?;
@@ -45,6 +247,8 @@ fn main() -> anyhow::Result<()> {
     )?;
     terminal.show_cursor()?;
 
+    data::remove_secondary_dirs();
+
     if let Err(err) = res {
         eprintln!("Error: {:?}", err);
     }
@@ -52,9 +256,9 @@ fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-fn run_app(terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>, mut app: App, db_path: &str) -> Result<App, std::io::Error> {
+fn run_app(terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>, mut tabs: tabs::Tabs) -> Result<tabs::Tabs, std::io::Error> {
     loop {
-        if app.data_manager.try_recv() {}
+        tabs.poll();
 
         let size = terminal.size()?;
         let chunks = ratatui::layout::Layout::default()
@@ -69,14 +273,27 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>, mut app:
             .split(size);
 
         if crossterm::event::poll(Duration::from_millis(50))? {
-            let event = event::read()?;
-            events::handle_event(event, &mut app, db_path, &chunks);
+            match event::read()? {
+                Event::Key(key) => {
+                    for input in tabs.active_mut().map_key(key) {
+                        if !events::handle_tab_input(&input, &mut tabs) {
+                            events::handle_input(input, tabs.active_mut());
+                        }
+                    }
+                }
+                event => events::handle_event(event, tabs.active_mut(), &chunks),
+            }
+        }
+        if tabs.should_quit() {
+            return Ok(tabs);
         }
-        if app.should_quit {
-            return Ok(app);
-    }        terminal.draw(|f| ui::ui(f, &mut app))?;
+        terminal.draw(|f| {
+            ui::ui(f, tabs.active_mut());
+            ui::draw_tab_bar(f, &tabs, chunks[0]);
+        })?;
+        let app = tabs.active();
 
-        if app.focus == Focus::Input {
+        if app.focus == Focus::Input && app.profile_picker.is_none() {
             let cursor_x = chunks[1].x + 1 + app.input.len() as u16;
             let cursor_y = chunks[1].y + 1;
             terminal.set_cursor(cursor_x, cursor_y)?;
@@ -1,4 +1,12 @@
+use crate::options::Ttl;
+use crate::timestamps::{as_epoch_seconds, format_epoch, DisplayZone, TimeFormat};
 use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+/// Extra columns of TTL databases, named so they cannot clash with decoded fields.
+pub const WRITTEN_COLUMN: &str = "@written";
+pub const EXPIRES_COLUMN: &str = "@expires_in";
 
 #[derive(Clone, Debug)]
 pub struct Record {
@@ -6,27 +14,144 @@ pub struct Record {
     pub key: String,
     pub data: Value,
     pub raw_data: Vec<u8>,
+    /// Write time in epoch seconds, from the suffix `DBWithTTL` appends to every value.
+    pub written_at: Option<i64>,
+    /// When the TTL runs out; the row stays readable until a compaction drops it.
+    pub expires_at: Option<i64>,
 }
 
 impl Record {
-    pub fn to_table_row(&self, all_headers: &[String]) -> Vec<String> {
+    /// The cells of `all_headers`; `formats` holds each column's time format, in the same order,
+    /// and timestamps are shown in `zone`.
+    pub fn to_table_row(&self, all_headers: &[String], formats: &[TimeFormat], zone: DisplayZone) -> Vec<String> {
         let mut row = vec![self.key.clone()];
 
-        if let Value::Object(map) = &self.data {
-            for header in &all_headers[1..] {
-                if let Some(value) = map.get(header) {
-                    row.push(value_to_string(value));
-                } else {
-                    row.push("".to_string());
-                }
-            }
-        } else {
-            for _ in 1..all_headers.len() {
-                row.push("".to_string());
-            }
+        for (i, header) in all_headers.iter().enumerate().skip(1) {
+            let format = formats.get(i).copied().unwrap_or_default();
+            row.push(match (header.as_str(), &self.data) {
+                (WRITTEN_COLUMN, _) => self.written_at.map(|t| format_epoch(t as f64, zone)).unwrap_or_default(),
+                (EXPIRES_COLUMN, _) => self.expires_at.map(format_expiry).unwrap_or_default(),
+                (_, Value::Object(map)) => map.get(header).map(|value| match as_epoch_seconds(header, value, format) {
+                    Some(seconds) => format_epoch(seconds, zone),
+                    None => value_to_string(value),
+                }).unwrap_or_default(),
+                _ => String::new(),
+            });
         }
         row
     }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at.is_some_and(|t| t <= chrono::Utc::now().timestamp())
+    }
+
+    /// The record as one JSON object: the key followed by the decoded fields.
+    pub fn row_json(&self) -> Value {
+        let mut map = serde_json::Map::new();
+        map.insert("key".to_string(), Value::String(self.key.clone()));
+        match &self.data {
+            Value::Object(fields) => map.extend(fields.clone()),
+            other => { map.insert("value".to_string(), other.clone()); }
+        }
+        Value::Object(map)
+    }
+
+    /// The value of a table column: the key for `key`, otherwise the decoded field.
+    pub fn field(&self, column: &str) -> Option<Value> {
+        match column {
+            "key" => return Some(Value::String(self.key.clone())),
+            WRITTEN_COLUMN => return self.written_at.map(Value::from),
+            EXPIRES_COLUMN => return self.expires_at.map(|t| Value::from(t - chrono::Utc::now().timestamp())),
+            _ => {}
+        }
+        match &self.data {
+            Value::Object(map) => map.get(column).cloned(),
+            _ => None,
+        }
+    }
+
+    pub fn pretty_value(&self) -> String {
+        serde_json::to_string_pretty(&self.data).unwrap_or_else(|_| self.data.to_string())
+    }
+}
+
+/// A field value normalised for sorting. Timestamps, whether epoch numbers or ISO-8601 strings,
+/// share one time key; other decimal strings compare as numbers; nulls and missing fields are
+/// kept apart so they can be placed last.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SortValue {
+    Bool(bool),
+    Number(f64),
+    Time(f64),
+    Text(String),
+    Null,
+}
+
+impl SortValue {
+    /// Reads `value` of `column`, which holds timestamps as `format` says.
+    pub fn from_value(column: &str, value: Option<&Value>, format: TimeFormat) -> Self {
+        let Some(value) = value else { return SortValue::Null };
+        if let Some(t) = as_epoch_seconds(column, value, format) {
+            return SortValue::Time(t);
+        }
+        match value {
+            Value::Null => SortValue::Null,
+            Value::Bool(b) => SortValue::Bool(*b),
+            Value::Number(n) => n.as_f64().map(SortValue::Number).unwrap_or(SortValue::Null),
+            Value::String(s) if is_decimal(s.trim()) => s.trim().parse().map(SortValue::Number).unwrap_or_else(|_| SortValue::Text(s.clone())),
+            Value::String(s) => SortValue::Text(s.clone()),
+            other => SortValue::Text(other.to_string()),
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, SortValue::Null)
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            SortValue::Bool(_) => 0,
+            SortValue::Number(_) | SortValue::Time(_) => 1,
+            SortValue::Text(_) => 2,
+            SortValue::Null => 3,
+        }
+    }
+
+    pub fn compare(&self, other: &SortValue) -> Ordering {
+        match (self, other) {
+            (SortValue::Bool(a), SortValue::Bool(b)) => a.cmp(b),
+            (SortValue::Number(a) | SortValue::Time(a), SortValue::Number(b) | SortValue::Time(b)) => a.total_cmp(b),
+            (SortValue::Text(a), SortValue::Text(b)) => a.cmp(b),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+/// Whether `s` is a plain decimal number such as `-12` or `3.5`, unlike `nan`, `inf` or `1e3`.
+fn is_decimal(s: &str) -> bool {
+    let digits = s.strip_prefix(['-', '+']).unwrap_or(s);
+    let (whole, fraction) = match digits.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (digits, None),
+    };
+    let all_digits = |part: &str| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit());
+    all_digits(whole) && fraction.is_none_or(all_digits)
+}
+
+/// Parses an ISO-8601 date or date-time into seconds since the Unix epoch (UTC when no offset is given).
+pub fn parse_timestamp(s: &str) -> Option<f64> {
+    let s = s.trim();
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(s) {
+        return Some(dt.timestamp_micros() as f64 / 1e6);
+    }
+    for format in ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"] {
+        if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(s, format) {
+            return Some(dt.and_utc().timestamp_micros() as f64 / 1e6);
+        }
+    }
+    chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|dt| dt.and_utc().timestamp() as f64)
 }
 
 fn value_to_string(value: &Value) -> String {
@@ -39,15 +164,197 @@ fn value_to_string(value: &Value) -> String {
     }
 }
 
-pub fn deserialize_record(key: &str, value: &[u8]) -> Record {
+/// How the values of a record type are turned into fields. Everything except `Json` yields a
+/// single `value` field.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Decoder {
+    /// JSON, falling back to the value as text.
+    #[default]
+    Json,
+    Utf8,
+    Hex,
+    U64Le,
+    U64Be,
+    I64Le,
+    I64Be,
+    F64Le,
+    Varint,
+}
+
+impl Decoder {
+    pub const NAMES: [&str; 9] = ["json", "utf8", "hex", "u64le", "u64be", "i64le", "i64be", "f64le", "varint"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "json" => Decoder::Json,
+            "utf8" => Decoder::Utf8,
+            "hex" => Decoder::Hex,
+            "u64le" => Decoder::U64Le,
+            "u64be" => Decoder::U64Be,
+            "i64le" => Decoder::I64Le,
+            "i64be" => Decoder::I64Be,
+            "f64le" => Decoder::F64Le,
+            "varint" => Decoder::Varint,
+            _ => return None,
+        })
+    }
+
+    /// Decodes `value`; values of the wrong length for a fixed-width decoder show as text.
+    pub fn decode(self, value: &[u8]) -> Value {
+        let text = || Value::String(String::from_utf8_lossy(value).to_string());
+        let fixed = <[u8; 8]>::try_from(value).ok();
+        let decoded = match self {
+            Decoder::Json => return serde_json::from_slice::<Value>(value).unwrap_or_else(|_| single_field(text())),
+            Decoder::Utf8 => text(),
+            Decoder::Hex => Value::String(value.iter().map(|b| format!("{:02x}", b)).collect()),
+            Decoder::U64Le => fixed.map(|b| Value::from(u64::from_le_bytes(b))).unwrap_or_else(text),
+            Decoder::U64Be => fixed.map(|b| Value::from(u64::from_be_bytes(b))).unwrap_or_else(text),
+            Decoder::I64Le => fixed.map(|b| Value::from(i64::from_le_bytes(b))).unwrap_or_else(text),
+            Decoder::I64Be => fixed.map(|b| Value::from(i64::from_be_bytes(b))).unwrap_or_else(text),
+            Decoder::F64Le => fixed.map(|b| Value::from(f64::from_le_bytes(b))).unwrap_or_else(text),
+            Decoder::Varint => match crate::hexdump::decode_varint(value) {
+                Some((n, len)) if len == value.len() => Value::from(n),
+                _ => text(),
+            },
+        };
+        single_field(decoded)
+    }
+}
+
+fn single_field(value: Value) -> Value {
+    Value::Object(serde_json::Map::from_iter([("value".to_string(), value)]))
+}
+
+/// Names the `:`-separated segments of a key, e.g. `order:{customer}:{id}`. The last
+/// segment takes whatever remains of the key.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeySchema {
+    segments: Vec<KeySegment>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum KeySegment {
+    Literal(String),
+    Field(String),
+}
+
+impl KeySchema {
+    pub fn parse(pattern: &str) -> Result<Self, String> {
+        let segments: Vec<KeySegment> = pattern.split(':').map(|segment| {
+            match segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+                Some(name) => KeySegment::Field(name.to_string()),
+                None => KeySegment::Literal(segment.to_string()),
+            }
+        }).collect();
+        if !segments.iter().any(|s| matches!(s, KeySegment::Field(_))) {
+            return Err(format!("key schema {} has no {{field}} placeholder", pattern));
+        }
+        Ok(Self { segments })
+    }
+
+    /// The named parts of `key`, or `None` when the key does not follow the schema.
+    pub fn fields(&self, key: &str) -> Option<Vec<(String, String)>> {
+        let mut parts = key.splitn(self.segments.len(), ':');
+        let mut fields = Vec::new();
+        for segment in &self.segments {
+            let part = parts.next()?;
+            match segment {
+                KeySegment::Literal(literal) if literal != part => return None,
+                KeySegment::Literal(_) => {}
+                KeySegment::Field(name) => fields.push((format!("key.{}", name), part.to_string())),
+            }
+        }
+        Some(fields)
+    }
+}
+
+/// Decoder and key schema configured for one record type.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RecordRules {
+    pub decoder: Decoder,
+    pub key_schema: Option<KeySchema>,
+}
+
+/// Rules by record type, as configured for one database.
+pub type RuleSet = BTreeMap<String, RecordRules>;
+
+/// Decodes a value with the rules configured for its record type.
+pub fn deserialize_record(rule_set: &RuleSet, key: &str, value: &[u8]) -> Record {
     let parts: Vec<&str> = key.split(':').collect();
     let record_type = parts.first().unwrap_or(&"unknown").to_string();
 
-    let data = if let Ok(v) = serde_json::from_slice::<Value>(value) {
-        v
-    } else {
-        Value::Object(serde_json::Map::from_iter(vec![("value".to_string(), Value::String(String::from_utf8_lossy(value).to_string()))]))
+    let rules = rule_set.get(&record_type);
+    let mut data = rules.map(|r| r.decoder).unwrap_or_default().decode(value);
+    if let Some(schema) = rules.and_then(|r| r.key_schema.as_ref()) && let Value::Object(map) = &mut data && let Some(fields) = schema.fields(key) {
+        for (name, part) in fields {
+            map.insert(name, Value::String(part));
+        }
+    }
+
+    Record { record_type, key: key.to_string(), data, raw_data: value.to_vec(), written_at: None, expires_at: None }
+}
+
+/// Decodes a value written through `DBWithTTL`: the trailing 4-byte little-endian write time
+/// is split off before the value is decoded.
+pub fn deserialize_ttl_record(rule_set: &RuleSet, key: &str, value: &[u8], ttl: Ttl) -> Record {
+    let Some((value, suffix)) = value.split_last_chunk::<4>() else {
+        return deserialize_record(rule_set, key, value);
     };
+    let written_at = i32::from_le_bytes(*suffix) as i64;
+    let mut record = deserialize_record(rule_set, key, value);
+    record.written_at = Some(written_at);
+    record.expires_at = (ttl.seconds > 0).then_some(written_at + ttl.seconds);
+    record
+}
 
-    Record { record_type, key: key.to_string(), data, raw_data: value.to_vec() }
-}
\ No newline at end of file
+/// Time left until `expires_at`, e.g. `2h 5m`, or how long ago it ran out.
+fn format_expiry(expires_at: i64) -> String {
+    let left = expires_at - chrono::Utc::now().timestamp();
+    if left <= 0 {
+        format!("expired {} ago", format_duration(-left))
+    } else {
+        format_duration(left)
+    }
+}
+
+fn format_duration(seconds: i64) -> String {
+    match seconds {
+        s if s < 60 => format!("{}s", s),
+        s if s < 3600 => format!("{}m {}s", s / 60, s % 60),
+        s if s < 86400 => format!("{}h {}m", s / 3600, s % 3600 / 60),
+        s => format!("{}d {}h", s / 86400, s % 86400 / 3600),
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sort(column: &str, values: &[Value], format: TimeFormat) -> Vec<Value> {
+        let mut sorted = values.to_vec();
+        sorted.sort_by(|a, b| SortValue::from_value(column, Some(a), format).compare(&SortValue::from_value(column, Some(b), format)));
+        sorted
+    }
+
+    #[test]
+    fn numeric_strings_must_be_decimal() {
+        assert_eq!(SortValue::from_value("n", Some(&json!("-12.5")), TimeFormat::Auto), SortValue::Number(-12.5));
+        for text in ["nan", "inf", "1e3", "1.", ".5", "-"] {
+            assert_eq!(SortValue::from_value("n", Some(&json!(text)), TimeFormat::Auto), SortValue::Text(text.to_string()));
+        }
+    }
+
+    #[test]
+    fn epoch_numbers_and_iso_strings_share_a_time_key() {
+        let values = [json!("2023-11-14T22:13:21Z"), json!(1700000000000u64), json!("2023-11-14T22:13:19Z")];
+        assert_eq!(sort("created_at", &values, TimeFormat::Auto), [values[2].clone(), values[1].clone(), values[0].clone()]);
+        assert_eq!(SortValue::from_value("created_at", Some(&json!(1700000000)), TimeFormat::Seconds), SortValue::Time(1700000000.0));
+    }
+
+    #[test]
+    fn nulls_and_missing_values_sort_after_everything() {
+        let values = [Value::Null, json!("b"), json!(2), json!(true), json!("a")];
+        assert_eq!(sort("n", &values, TimeFormat::Auto), [json!(true), json!(2), json!("a"), json!("b"), Value::Null]);
+        assert!(SortValue::from_value("n", None, TimeFormat::Auto).is_null());
+    }
+}
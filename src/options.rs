@@ -0,0 +1,429 @@
+use rocksdb::{Cache, ColumnFamilyDescriptor, Env, MergeOperands, Options, DB};
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+
+/// How the viewer opens a database for browsing.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum OpenMode {
+    #[default]
+    ReadOnly,
+    /// A secondary instance, which also sees writes the primary has not flushed yet.
+    Secondary,
+    /// Read-only browsing plus the admin menu and deletes.
+    Writable,
+}
+
+impl OpenMode {
+    pub const NAMES: [&str; 3] = ["read-only", "secondary", "writable"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "read-only" => Some(OpenMode::ReadOnly),
+            "secondary" => Some(OpenMode::Secondary),
+            "writable" => Some(OpenMode::Writable),
+            _ => None,
+        }
+    }
+
+    /// Like `from_name`, with an error that lists the modes; used for `--mode` and the config file.
+    pub fn parse(name: &str) -> Result<Self, String> {
+        Self::from_name(name).ok_or_else(|| format!("unknown mode {}; expected one of {}", name, Self::NAMES.join(", ")))
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            OpenMode::ReadOnly => "read-only",
+            OpenMode::Secondary => "secondary",
+            OpenMode::Writable => "writable",
+        }
+    }
+}
+
+/// Settings forced for a database by the command line or its profile: the comparator and
+/// merge operator for databases whose OPTIONS file is missing or uses custom names, the TTL
+/// and the open mode.
+#[derive(Clone, Debug, Default)]
+pub struct Overrides {
+    pub comparator: Option<String>,
+    pub merge_operator: Option<String>,
+    pub ttl: Option<i64>,
+    pub mode: OpenMode,
+}
+
+pub const COMPARATORS: [&str; 3] = ["bytewise", "reverse", "u64"];
+pub const MERGE_OPERATORS: [&str; 5] = ["uint64add", "counter", "append", "max", "put"];
+
+/// The newest `OPTIONS-<number>` file in the database directory.
+pub fn latest_options_file(db_path: &str) -> Option<PathBuf> {
+    std::fs::read_dir(db_path)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let number: u64 = name.strip_prefix("OPTIONS-")?.parse().ok()?;
+            Some((number, entry.path()))
+        })
+        .max_by_key(|(number, _)| *number)
+        .map(|(_, path)| path)
+}
+
+/// One `[CFOptions "name"]` section of an OPTIONS file: the column family and its name/value pairs.
+pub type CfSection = (String, Vec<(String, String)>);
+
+pub fn read_cf_sections(path: &Path) -> Result<Vec<CfSection>, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut sections: Vec<CfSection> = Vec::new();
+    let mut in_cf = false;
+    for line in text.lines().map(str::trim) {
+        if line.starts_with('[') {
+            let name = line.strip_prefix("[CFOptions \"").and_then(|rest| rest.strip_suffix("\"]"));
+            in_cf = name.is_some();
+            if let Some(name) = name {
+                sections.push((name.to_string(), Vec::new()));
+            }
+        } else if in_cf && let Some((name, value)) = line.split_once('=') && let Some(section) = sections.last_mut() {
+            section.1.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+    Ok(sections)
+}
+
+/// The class name of a serialized customizable option: `{id=StringAppendOperator;delimiter=,}`
+/// and `StringAppendOperator` both give `StringAppendOperator`.
+fn option_id(value: &str) -> &str {
+    let inner = value.trim().trim_start_matches('{').trim_end_matches('}');
+    inner.split(';').find_map(|part| part.strip_prefix("id=")).unwrap_or(inner)
+}
+
+fn option_field<'a>(value: &'a str, field: &str) -> Option<&'a str> {
+    let inner = value.trim().trim_start_matches('{').trim_end_matches('}');
+    inner.split(';').find_map(|part| part.strip_prefix(field)?.strip_prefix('='))
+}
+
+/// How the keys of a column family are ordered.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum KeyOrder {
+    #[default]
+    Bytewise,
+    Reverse,
+    U64,
+}
+
+impl KeyOrder {
+    fn from_kind(kind: &str) -> Result<Self, String> {
+        match kind {
+            "bytewise" => Ok(KeyOrder::Bytewise),
+            "reverse" => Ok(KeyOrder::Reverse),
+            "u64" => Ok(KeyOrder::U64),
+            other => Err(format!("unknown comparator kind {}; expected one of {}", other, COMPARATORS.join(", "))),
+        }
+    }
+
+    /// The order of one of RocksDB's built-in comparators.
+    fn of_builtin(name: &str) -> Option<Self> {
+        match name {
+            "leveldb.BytewiseComparator" => Some(KeyOrder::Bytewise),
+            "rocksdb.ReverseBytewiseComparator" => Some(KeyOrder::Reverse),
+            "rocksdb.Uint64Comparator" => Some(KeyOrder::U64),
+            _ => None,
+        }
+    }
+
+    fn builtin_name(self) -> &'static str {
+        match self {
+            KeyOrder::Bytewise => "leveldb.BytewiseComparator",
+            KeyOrder::Reverse => "rocksdb.ReverseBytewiseComparator",
+            KeyOrder::U64 => "rocksdb.Uint64Comparator",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            KeyOrder::Bytewise => "bytewise",
+            KeyOrder::Reverse => "reverse",
+            KeyOrder::U64 => "u64",
+        }
+    }
+
+    pub fn compare(self, a: &[u8], b: &[u8]) -> Ordering {
+        match self {
+            KeyOrder::Bytewise => a.cmp(b),
+            KeyOrder::Reverse => b.cmp(a),
+            KeyOrder::U64 => compare_u64(a, b),
+        }
+    }
+}
+
+/// What a comparator or merge operator override did for one column family.
+#[derive(Clone, Debug, PartialEq)]
+enum Override {
+    Unused,
+    Applied,
+    /// The column family records this known name, which orders or merges differently.
+    Conflicts(String),
+}
+
+/// The order of a column family's comparator. The override only supplies it for a comparator
+/// that is not recorded or not known; a known one always wins.
+fn resolve_comparator(name: &str, kind: Option<&str>) -> Result<(KeyOrder, Override), String> {
+    match (KeyOrder::of_builtin(name), kind) {
+        (Some(order), Some(kind)) if KeyOrder::from_kind(kind)? != order => Ok((order, Override::Conflicts(name.to_string()))),
+        (Some(order), _) => Ok((order, Override::Unused)),
+        (None, Some(kind)) => Ok((KeyOrder::from_kind(kind)?, Override::Applied)),
+        (None, None) if name.is_empty() => Ok((KeyOrder::Bytewise, Override::Unused)),
+        (None, None) => Err(format!("unknown comparator {}; pass --comparator with one of {}", name, COMPARATORS.join(", "))),
+    }
+}
+
+/// Installs the comparator of a column family. RocksDB refuses to open a database under a
+/// comparator with a different name, so a recorded custom name is kept and only the order is
+/// supplied.
+fn apply_comparator(opts: &mut Options, name: &str, order: KeyOrder) {
+    let name = if name.is_empty() || KeyOrder::of_builtin(name).is_some() { order.builtin_name() } else { name };
+    if name != KeyOrder::Bytewise.builtin_name() {
+        opts.set_comparator(name, Box::new(move |a: &[u8], b: &[u8]| order.compare(a, b)));
+    }
+}
+
+/// Keys of exactly eight bytes compare as native-endian u64, like RocksDB's Uint64Comparator.
+fn compare_u64(a: &[u8], b: &[u8]) -> Ordering {
+    match (<[u8; 8]>::try_from(a), <[u8; 8]>::try_from(b)) {
+        (Ok(a), Ok(b)) => u64::from_ne_bytes(a).cmp(&u64::from_ne_bytes(b)),
+        _ => a.cmp(b),
+    }
+}
+
+/// The built-in operator to merge a column family's values with, if any. Like the comparator,
+/// the override only supplies one when none is recorded or the recorded one is not known.
+fn resolve_merge_operator<'a>(id: &str, kind: Option<&'a str>) -> Result<(Option<&'a str>, Override), String> {
+    let known = match id {
+        "UInt64AddOperator" | "uint64add" => Some("uint64add"),
+        "StringAppendOperator" | "StringAppendTESTOperator" | "stringappend" => Some("append"),
+        "MaxOperator" | "max" => Some("max"),
+        "PutOperator" | "put" => Some("put"),
+        _ => None,
+    };
+    match (known, kind) {
+        (_, Some(kind)) if !MERGE_OPERATORS.contains(&kind) => {
+            Err(format!("unknown merge operator {}; expected one of {}", kind, MERGE_OPERATORS.join(", ")))
+        }
+        (Some(known), Some(kind)) if known != kind => Ok((Some(known), Override::Conflicts(id.to_string()))),
+        (Some(known), _) => Ok((Some(known), Override::Unused)),
+        (None, Some(kind)) => Ok((Some(kind), Override::Applied)),
+        // Unknown operators only matter once a merged key is read, so opening still goes ahead
+        (None, None) => Ok((None, Override::Unused)),
+    }
+}
+
+/// Installs a built-in merge operator so merge operands resolve on read. The operators are
+/// associative, so the same function serves full and partial merges.
+fn apply_merge_operator(opts: &mut Options, recorded: &str, kind: &str) {
+    let id = option_id(recorded);
+    let name = if id.is_empty() || id == "nullptr" { kind } else { id };
+    let kind = kind.to_string();
+    let delimiter = option_field(recorded, "delimiter").unwrap_or(",").as_bytes().to_vec();
+    opts.set_merge_operator_associative(name, move |_: &[u8], existing: Option<&[u8]>, operands: &MergeOperands| {
+        merge_values(&kind, &delimiter, existing.into_iter().chain(operands))
+    });
+}
+
+/// Merges the existing value, if any, followed by the operands, with one of `MERGE_OPERATORS`.
+fn merge_values<'a>(kind: &str, delimiter: &[u8], mut values: impl Iterator<Item = &'a [u8]>) -> Option<Vec<u8>> {
+    match kind {
+        // Sums 8-byte little-endian counters, like RocksDB's UInt64AddOperator
+        "uint64add" => {
+            let sum = values.fold(0u64, |sum, value| sum.wrapping_add(u64::from_le_bytes(<[u8; 8]>::try_from(value).unwrap_or_default())));
+            Some(sum.to_le_bytes().to_vec())
+        }
+        // Sums counters stored as decimal text, which keeps them readable in the table
+        "counter" => {
+            let sum = values.fold(0i64, |sum, value| sum.wrapping_add(String::from_utf8_lossy(value).trim().parse().unwrap_or(0)));
+            Some(sum.to_string().into_bytes())
+        }
+        "append" => {
+            let mut merged = values.next()?.to_vec();
+            for part in values {
+                merged.extend_from_slice(delimiter);
+                merged.extend_from_slice(part);
+            }
+            Some(merged)
+        }
+        "max" => values.max().map(<[u8]>::to_vec),
+        // The last operand wins, or the existing value without operands
+        "put" => values.last().map(<[u8]>::to_vec),
+        _ => None,
+    }
+}
+
+/// TTL of a database written through `DBWithTTL`; zero or less means values never expire.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ttl {
+    pub seconds: i64,
+}
+
+/// The TTL given with `--ttl`, or the one recorded with the TTL compaction filter factory in the OPTIONS file.
+pub fn detect_ttl(db_path: &str, overrides: &Overrides) -> Option<Ttl> {
+    if let Some(seconds) = overrides.ttl {
+        return Some(Ttl { seconds });
+    }
+    let sections = read_cf_sections(&latest_options_file(db_path)?).ok()?;
+    sections.iter().flat_map(|(_, options)| options).find_map(|(name, value)| {
+        if name != "compaction_filter_factory" || option_id(value) != "TtlCompactionFilterFactory" {
+            return None;
+        }
+        let seconds = option_field(value, "ttl").and_then(|ttl| ttl.parse().ok()).unwrap_or(0);
+        Some(Ttl { seconds })
+    })
+}
+
+/// Database options and one descriptor per column family. When the viewer has nothing to
+/// supply, every option comes from the newest OPTIONS file; otherwise the comparator and
+/// merge operator recorded there (or forced on the command line) are installed.
+pub fn open_options(db_path: &str, overrides: &Overrides) -> Result<(Options, Vec<ColumnFamilyDescriptor>), String> {
+    let mut opts = Options::default();
+    opts.create_if_missing(false);
+    let sections = recorded_sections(db_path)?;
+    let cf_names = DB::list_cf(&opts, db_path).unwrap_or_else(|_| vec!["default".to_string()]);
+    let cfs = cf_names.iter().map(|name| build_cf_options(&sections, name, overrides)).collect::<Result<Vec<_>, _>>()?;
+    check_override("--comparator", overrides.comparator.as_deref(), cfs.iter().map(|cf| &cf.comparator))?;
+    check_override("--merge-operator", overrides.merge_operator.as_deref(), cfs.iter().map(|cf| &cf.merge_operator))?;
+
+    let supplied = cfs.iter().any(|cf| cf.comparator == Override::Applied || cf.merge_operator == Override::Applied);
+    if !supplied && let Ok(env) = Env::new()
+        && let Ok((mut loaded, descriptors)) = Options::load_latest(db_path, env, false, Cache::new_lru_cache(8 << 20)) {
+        loaded.create_if_missing(false);
+        return Ok((loaded, descriptors));
+    }
+    let descriptors = cf_names.into_iter().zip(cfs).map(|(name, cf)| ColumnFamilyDescriptor::new(name, cf.opts)).collect();
+    Ok((opts, descriptors))
+}
+
+/// An override that no column family needs, but that disagrees with a recorded built-in, is
+/// most likely meant for a different database.
+fn check_override<'a>(flag: &str, kind: Option<&str>, results: impl Iterator<Item = &'a Override>) -> Result<(), String> {
+    let Some(kind) = kind else { return Ok(()) };
+    let mut conflict = None;
+    for result in results {
+        match result {
+            Override::Applied => return Ok(()),
+            Override::Conflicts(name) => conflict = conflict.or(Some(name)),
+            Override::Unused => {}
+        }
+    }
+    match conflict {
+        Some(name) => Err(format!("{} {} conflicts with the recorded {}; it only applies where none or an unknown one is recorded", flag, kind, name)),
+        None => Ok(()),
+    }
+}
+
+/// The options `open_options` gives one column family, e.g. for a copy that has to keep its
+/// comparator and merge operator.
+pub fn cf_options(db_path: &str, cf: &str, overrides: &Overrides) -> Result<Options, String> {
+    build_cf_options(&recorded_sections(db_path)?, cf, overrides).map(|cf| cf.opts)
+}
+
+/// The key order of a column family, for code that walks keys side by side or seeks to a prefix.
+pub fn key_order(db_path: &str, cf: &str, overrides: &Overrides) -> Result<KeyOrder, String> {
+    let sections = recorded_sections(db_path)?;
+    resolve_comparator(option_id(&recorded(&sections, cf, "comparator")), overrides.comparator.as_deref()).map(|(order, _)| order)
+}
+
+/// Options for `DB::repair` around a lone SST file. Nothing is recorded for it yet, so the
+/// comparator and merge operator come from the overrides, and the rebuilt manifest keeps them.
+pub fn repair_options(overrides: &Overrides) -> Result<Options, String> {
+    build_cf_options(&[], "default", overrides).map(|cf| cf.opts)
+}
+
+fn recorded_sections(db_path: &str) -> Result<Vec<CfSection>, String> {
+    match latest_options_file(db_path) {
+        Some(path) => read_cf_sections(&path),
+        None => Ok(Vec::new()),
+    }
+}
+
+fn recorded(sections: &[CfSection], cf: &str, option: &str) -> String {
+    sections.iter()
+        .find(|(name, _)| name == cf)
+        .and_then(|(_, options)| options.iter().find(|(n, _)| n == option))
+        .map(|(_, value)| value.clone())
+        .unwrap_or_default()
+}
+
+/// The options of one column family and what the overrides did for it.
+struct CfOptions {
+    opts: Options,
+    comparator: Override,
+    merge_operator: Override,
+}
+
+fn build_cf_options(sections: &[CfSection], cf: &str, overrides: &Overrides) -> Result<CfOptions, String> {
+    let mut opts = Options::default();
+    let comparator = recorded(sections, cf, "comparator");
+    let (order, comparator_override) = resolve_comparator(option_id(&comparator), overrides.comparator.as_deref())?;
+    apply_comparator(&mut opts, option_id(&comparator), order);
+    let merge_operator = recorded(sections, cf, "merge_operator");
+    let (kind, merge_override) = resolve_merge_operator(option_id(&merge_operator), overrides.merge_operator.as_deref())?;
+    if let Some(kind) = kind {
+        apply_merge_operator(&mut opts, &merge_operator, kind);
+    }
+    Ok(CfOptions { opts, comparator: comparator_override, merge_operator: merge_override })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merge(kind: &str, values: &[&[u8]]) -> Option<Vec<u8>> {
+        merge_values(kind, b",", values.iter().copied())
+    }
+
+    #[test]
+    fn compare_u64_orders_eight_byte_keys_numerically() {
+        let (one, two, big) = (1u64.to_ne_bytes(), 2u64.to_ne_bytes(), 256u64.to_ne_bytes());
+        assert_eq!(compare_u64(&one, &two), Ordering::Less);
+        assert_eq!(compare_u64(&big, &two), Ordering::Greater);
+        assert_eq!(compare_u64(&two, &two), Ordering::Equal);
+        // Other lengths fall back to bytewise order
+        assert_eq!(compare_u64(b"ab", b"b"), Ordering::Less);
+    }
+
+    #[test]
+    fn merge_operators() {
+        let sum = merge("uint64add", &[&5u64.to_le_bytes(), &7u64.to_le_bytes()]);
+        assert_eq!(sum, Some(12u64.to_le_bytes().to_vec()));
+        assert_eq!(merge("counter", &[b"5", b" -2 ", b"x"]), Some(b"3".to_vec()));
+        assert_eq!(merge("append", &[b"a", b"b", b"c"]), Some(b"a,b,c".to_vec()));
+        assert_eq!(merge("append", &[]), None);
+        assert_eq!(merge("max", &[b"b", b"c", b"a"]), Some(b"c".to_vec()));
+        assert_eq!(merge("put", &[b"old", b"new"]), Some(b"new".to_vec()));
+    }
+
+    #[test]
+    fn comparator_override_only_applies_to_missing_or_unknown_names() {
+        assert_eq!(resolve_comparator("leveldb.BytewiseComparator", None), Ok((KeyOrder::Bytewise, Override::Unused)));
+        assert_eq!(resolve_comparator("my.Comparator", Some("u64")), Ok((KeyOrder::U64, Override::Applied)));
+        assert_eq!(resolve_comparator("", Some("reverse")), Ok((KeyOrder::Reverse, Override::Applied)));
+        assert_eq!(
+            resolve_comparator("leveldb.BytewiseComparator", Some("u64")),
+            Ok((KeyOrder::Bytewise, Override::Conflicts("leveldb.BytewiseComparator".to_string())))
+        );
+        assert!(resolve_comparator("my.Comparator", None).is_err());
+    }
+
+    #[test]
+    fn merge_operator_override_only_applies_to_missing_or_unknown_names() {
+        assert_eq!(resolve_merge_operator("nullptr", Some("counter")), Ok((Some("counter"), Override::Applied)));
+        assert_eq!(resolve_merge_operator("MaxOperator", Some("max")), Ok((Some("max"), Override::Unused)));
+        assert_eq!(resolve_merge_operator("MaxOperator", Some("put")), Ok((Some("max"), Override::Conflicts("MaxOperator".to_string()))));
+        assert_eq!(resolve_merge_operator("MyOperator", None), Ok((None, Override::Unused)));
+        assert!(resolve_merge_operator("MyOperator", Some("sum")).is_err());
+    }
+
+    #[test]
+    fn conflicting_override_is_rejected_unless_a_column_family_needs_it() {
+        let conflict = Override::Conflicts("leveldb.BytewiseComparator".to_string());
+        assert!(check_override("--comparator", Some("u64"), [&conflict].into_iter()).is_err());
+        assert!(check_override("--comparator", Some("u64"), [&conflict, &Override::Applied].into_iter()).is_ok());
+        assert!(check_override("--comparator", None, [&conflict].into_iter()).is_ok());
+    }
+}
@@ -0,0 +1,55 @@
+use crate::config::Config;
+
+/// What the picker opens once an entry is chosen.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PickerChoice {
+    Profile(String),
+    Path(String),
+}
+
+/// The database picker: the profiles of the config file plus a last entry for typing a path.
+pub struct ProfilePicker {
+    /// Profile names with a short description of the database each opens.
+    pub entries: Vec<(String, String)>,
+    pub index: usize,
+    /// The path being typed after choosing the last entry.
+    pub path_input: Option<String>,
+    /// Shown at startup when no database was given; Esc quits instead of closing it.
+    pub startup: bool,
+}
+
+impl ProfilePicker {
+    pub fn new(config: &Config, current: Option<&str>, startup: bool) -> Self {
+        let entries: Vec<(String, String)> = config.profiles.iter().map(|(name, profile)| {
+            let settings = profile.settings.clone().or(&config.defaults);
+            let path = settings.db_path.unwrap_or_else(|| "(no db_path)".to_string());
+            (name.clone(), format!("{}  [{}]", path, settings.mode.unwrap_or_default().label()))
+        }).collect();
+        let index = current.and_then(|name| entries.iter().position(|(n, _)| n == name)).unwrap_or(0);
+        Self { entries, index, path_input: None, startup }
+    }
+
+    /// Number of rows, including the "open a path" entry.
+    pub fn row_count(&self) -> usize {
+        self.entries.len() + 1
+    }
+
+    pub fn select(&mut self, delta: isize) {
+        self.index = self.index.saturating_add_signed(delta).min(self.row_count() - 1);
+    }
+
+    /// The chosen entry, or `None` when the path entry was chosen and a path prompt opened.
+    pub fn choose(&mut self) -> Option<PickerChoice> {
+        if let Some(path) = self.path_input.take() {
+            let path = path.trim().to_string();
+            return (!path.is_empty()).then_some(PickerChoice::Path(path));
+        }
+        match self.entries.get(self.index) {
+            Some((name, _)) => Some(PickerChoice::Profile(name.clone())),
+            None => {
+                self.path_input = Some(String::new());
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use crate::options::Overrides;
+use rocksdb::{LiveFile, DB};
+use std::path::{Path, PathBuf};
+
+/// Live SST files of all column families, ordered by column family, level and file name.
+pub fn live_files(db_path: &str, overrides: &Overrides) -> Result<Vec<LiveFile>, String> {
+    let (db, _) = crate::data::open_read_only(db_path, overrides).map_err(|e| e.to_string())?;
+    let mut files = db.live_files().map_err(|e| e.to_string())?;
+    files.sort_by(|a, b| {
+        (&a.column_family_name, a.level, &a.name).cmp(&(&b.column_family_name, b.level, &b.name))
+    });
+    Ok(files)
+}
+
+/// Renders a boundary key, escaping bytes that are not printable ASCII.
+pub fn display_key(key: &Option<Vec<u8>>) -> String {
+    match key {
+        Some(bytes) => bytes.iter().flat_map(|&b| std::ascii::escape_default(b)).map(|b| b as char).collect(),
+        None => "-".to_string(),
+    }
+}
+
+/// A standalone `.sst` file staged as a database in a temporary directory, which is removed
+/// when this is dropped, including on errors and panics.
+pub struct StagedSst {
+    dir: PathBuf,
+}
+
+impl StagedSst {
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+}
+
+impl Drop for StagedSst {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Makes a standalone `.sst` file browsable: the file is copied into an empty temporary
+/// directory and `DB::repair` rebuilds a manifest around it, after which the directory opens
+/// like any other database. The manifest records the comparator given with `--comparator`.
+pub fn stage_sst_file(path: &Path, overrides: &Overrides) -> anyhow::Result<StagedSst> {
+    let dir = std::env::temp_dir().join(format!("rocksdb-viewer-sst-{}", std::process::id()));
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir)?;
+    }
+    std::fs::create_dir_all(&dir)?;
+    let staged = StagedSst { dir };
+    std::fs::copy(path, staged.dir.join("000001.sst"))?;
+    let opts = crate::options::repair_options(overrides).map_err(anyhow::Error::msg)?;
+    DB::repair(&opts, &staged.dir).map_err(|e| anyhow::anyhow!("cannot read {}: {}", path.display(), e))?;
+    Ok(staged)
+}
@@ -0,0 +1,68 @@
+use crate::app::App;
+
+/// The open tabs, each an independent app with its own database, table, filter and
+/// background loader.
+pub struct Tabs {
+    pub apps: Vec<App>,
+    pub active: usize,
+}
+
+impl Tabs {
+    pub fn new(app: App) -> Self {
+        Self { apps: vec![app], active: 0 }
+    }
+
+    pub fn active(&self) -> &App {
+        &self.apps[self.active]
+    }
+
+    pub fn active_mut(&mut self) -> &mut App {
+        &mut self.apps[self.active]
+    }
+
+    /// Opens a tab on the active tab's database and offers the picker to open another one there.
+    pub fn open_tab(&mut self) {
+        let mut app = self.active().duplicate();
+        app.open_profile_picker();
+        self.active += 1;
+        self.apps.insert(self.active, app);
+    }
+
+    /// Closes the active tab; closing the last one quits.
+    pub fn close_active(&mut self) {
+        if self.apps.len() == 1 {
+            self.apps[0].should_quit = true;
+            return;
+        }
+        self.apps.remove(self.active);
+        self.active = self.active.min(self.apps.len() - 1);
+    }
+
+    pub fn select(&mut self, index: usize) {
+        if index < self.apps.len() {
+            self.active = index;
+        }
+    }
+
+    pub fn cycle(&mut self, delta: isize) {
+        self.active = self.active.checked_add_signed(delta).unwrap_or(self.apps.len() - 1) % self.apps.len();
+    }
+
+    /// Picks up new data and finished background work in every tab, so inactive tabs stay current.
+    pub fn poll(&mut self) {
+        for app in &mut self.apps {
+            app.data_manager.try_recv();
+            app.poll_background();
+            app.drop_stale_marks();
+        }
+    }
+
+    /// Closes the active tab once it has been asked to quit; only the last tab quits.
+    pub fn should_quit(&mut self) -> bool {
+        if !self.active().should_quit {
+            return false;
+        }
+        self.close_active();
+        self.apps.len() == 1 && self.apps[0].should_quit
+    }
+}
@@ -0,0 +1,263 @@
+use chrono::{DateTime, FixedOffset, Local, SecondsFormat, TimeZone, Utc};
+use serde_json::Value;
+
+/// How a column's values are read as points in time. `Auto` recognises date strings, and numbers
+/// in the plausible epoch range for columns whose name suggests a time.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TimeFormat {
+    #[default]
+    Auto,
+    Off,
+    Seconds,
+    Millis,
+    Micros,
+    Nanos,
+}
+
+impl TimeFormat {
+    pub const ALL: [TimeFormat; 6] = [
+        TimeFormat::Auto,
+        TimeFormat::Off,
+        TimeFormat::Seconds,
+        TimeFormat::Millis,
+        TimeFormat::Micros,
+        TimeFormat::Nanos,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TimeFormat::Auto => "auto",
+            TimeFormat::Off => "off",
+            TimeFormat::Seconds => "s",
+            TimeFormat::Millis => "ms",
+            TimeFormat::Micros => "us",
+            TimeFormat::Nanos => "ns",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|f| f.label() == label)
+    }
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|f| *f == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    fn per_second(self) -> Option<f64> {
+        match self {
+            TimeFormat::Seconds => Some(1.0),
+            TimeFormat::Millis => Some(1e3),
+            TimeFormat::Micros => Some(1e6),
+            TimeFormat::Nanos => Some(1e9),
+            TimeFormat::Auto | TimeFormat::Off => None,
+        }
+    }
+}
+
+/// Guesses the unit of an epoch number from its magnitude, accepting only 2001 to 2286.
+fn guess_unit(n: f64) -> Option<TimeFormat> {
+    [TimeFormat::Seconds, TimeFormat::Millis, TimeFormat::Micros, TimeFormat::Nanos]
+        .into_iter()
+        .find(|unit| (1e9..1e10).contains(&(n.abs() / unit.per_second().unwrap_or(1.0))))
+}
+
+fn looks_like_time_column(column: &str) -> bool {
+    let column = column.to_ascii_lowercase();
+    ["time", "date", "_at", "_ts", "ts_", "created", "updated", "modified", "expires", "epoch"]
+        .iter()
+        .any(|hint| column.contains(hint))
+        || column == "ts"
+}
+
+/// The value of `column` as seconds since the Unix epoch, if it is a timestamp under `format`.
+pub fn as_epoch_seconds(column: &str, value: &Value, format: TimeFormat) -> Option<f64> {
+    let number = match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse::<f64>().ok(),
+        _ => None,
+    };
+    match (format, number) {
+        (TimeFormat::Off, _) => None,
+        (TimeFormat::Auto, Some(n)) if looks_like_time_column(column) => guess_unit(n).and_then(TimeFormat::per_second).map(|p| n / p),
+        (TimeFormat::Auto, Some(_)) => None,
+        (TimeFormat::Auto, None) => value.as_str().and_then(crate::models::parse_timestamp),
+        (unit, Some(n)) => Some(n / unit.per_second()?),
+        (_, None) => value.as_str().and_then(crate::models::parse_timestamp),
+    }
+}
+
+/// The zone timestamps are shown in, chosen with `--tz` or a profile's `tz`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DisplayZone {
+    #[default]
+    Local,
+    Fixed(FixedOffset),
+    Named(chrono_tz::Tz),
+}
+
+/// Accepts `local`, `utc`, an offset such as `+05:30`, or an IANA name such as `Europe/Berlin`.
+pub fn parse_zone(s: &str) -> Result<DisplayZone, String> {
+    match s.trim() {
+        "local" => Ok(DisplayZone::Local),
+        "utc" | "UTC" | "Z" => Ok(DisplayZone::Fixed(FixedOffset::east_opt(0).unwrap())),
+        s if s.starts_with(['+', '-']) => {
+            let (hours, minutes) = s[1..].split_once(':').unwrap_or((&s[1..], "0"));
+            let seconds = hours.parse::<i32>().ok().zip(minutes.parse::<i32>().ok()).map(|(h, m)| h * 3600 + m * 60);
+            let sign = if s.starts_with('-') { -1 } else { 1 };
+            seconds.and_then(|seconds| FixedOffset::east_opt(sign * seconds)).map(DisplayZone::Fixed).ok_or_else(|| format!("invalid offset {}", s))
+        }
+        s => s.parse::<chrono_tz::Tz>().map(DisplayZone::Named).map_err(|_| format!("unknown time zone {}", s)),
+    }
+}
+
+/// Formats epoch seconds as RFC 3339 in `zone`, with sub-second digits only when present.
+pub fn format_epoch(seconds: f64, zone: DisplayZone) -> String {
+    let Some(utc) = DateTime::<Utc>::from_timestamp_micros((seconds * 1e6).round() as i64) else {
+        return seconds.to_string();
+    };
+    match zone {
+        DisplayZone::Local => utc.with_timezone(&Local).to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        DisplayZone::Fixed(offset) => utc.with_timezone(&offset).to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        DisplayZone::Named(tz) => utc.with_timezone(&tz).to_rfc3339_opts(SecondsFormat::AutoSi, true),
+    }
+}
+
+/// Parses a filter bound: a date or date-time (read in `zone` unless it carries an offset),
+/// `now`, or `now-<n><s|m|h|d>`.
+pub fn parse_bound(s: &str, zone: DisplayZone) -> Option<f64> {
+    let now = Utc::now().timestamp() as f64;
+    if s == "now" {
+        return Some(now);
+    }
+    if let Some(ago) = s.strip_prefix("now-") {
+        let unit = match ago.chars().last()? {
+            's' => 1.0,
+            'm' => 60.0,
+            'h' => 3600.0,
+            'd' => 86400.0,
+            _ => return None,
+        };
+        return Some(now - ago[..ago.len() - 1].parse::<f64>().ok()? * unit);
+    }
+    if DateTime::parse_from_rfc3339(s).is_ok() {
+        return crate::models::parse_timestamp(s);
+    }
+    // Without an offset, parse_timestamp reads the time as UTC; shift it into the display zone
+    let naive = DateTime::<Utc>::from_timestamp_micros((crate::models::parse_timestamp(s)? * 1e6) as i64)?.naive_utc();
+    let local = match zone {
+        DisplayZone::Local => Local.from_local_datetime(&naive).earliest()?.timestamp_micros(),
+        DisplayZone::Fixed(offset) => offset.from_local_datetime(&naive).earliest()?.timestamp_micros(),
+        DisplayZone::Named(tz) => tz.from_local_datetime(&naive).earliest()?.timestamp_micros(),
+    };
+    Some(local as f64 / 1e6)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Comparison {
+    Before,
+    AtOrBefore,
+    After,
+    AtOrAfter,
+}
+
+/// One `column>=bound` term of the filter input.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimeCondition {
+    pub column: String,
+    comparison: Comparison,
+    bound: f64,
+}
+
+impl TimeCondition {
+    pub fn matches(&self, seconds: f64) -> bool {
+        match self.comparison {
+            Comparison::Before => seconds < self.bound,
+            Comparison::AtOrBefore => seconds <= self.bound,
+            Comparison::After => seconds > self.bound,
+            Comparison::AtOrAfter => seconds >= self.bound,
+        }
+    }
+
+    fn parse(term: &str, zone: DisplayZone) -> Option<Self> {
+        let at = term.find(['<', '>'])?;
+        let column = &term[..at];
+        if column.is_empty() || !column.chars().all(|c| c.is_alphanumeric() || matches!(c, '_' | '.' | '@' | '-')) {
+            return None;
+        }
+        let rest = &term[at..];
+        let (comparison, bound) = if let Some(bound) = rest.strip_prefix(">=") {
+            (Comparison::AtOrAfter, bound)
+        } else if let Some(bound) = rest.strip_prefix("<=") {
+            (Comparison::AtOrBefore, bound)
+        } else if let Some(bound) = rest.strip_prefix('>') {
+            (Comparison::After, bound)
+        } else {
+            (Comparison::Before, &rest[1..])
+        };
+        Some(Self { column: column.to_string(), comparison, bound: parse_bound(bound, zone)? })
+    }
+}
+
+/// Splits the filter input into time conditions, with bounds read in `zone`, and the remaining
+/// text, which still filters keys.
+pub fn parse_filter(input: &str, zone: DisplayZone) -> (Vec<TimeCondition>, String) {
+    let mut conditions = Vec::new();
+    let mut rest = Vec::new();
+    for term in input.split_whitespace() {
+        match TimeCondition::parse(term, zone) {
+            Some(condition) => conditions.push(condition),
+            None => rest.push(term),
+        }
+    }
+    // Without any condition the input is a plain key filter and keeps its spaces
+    if conditions.is_empty() {
+        return (conditions, input.to_string());
+    }
+    (conditions, rest.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_bound_reads_offsets_and_relative_times() {
+        assert_eq!(parse_bound("2024-01-31T10:00:00+02:00", DisplayZone::Local), Some(1706688000.0));
+        assert_eq!(parse_bound("2024-01-31T08:00:00Z", DisplayZone::Local), Some(1706688000.0));
+        let now = Utc::now().timestamp() as f64;
+        let hour_ago = parse_bound("now-1h", DisplayZone::Local).unwrap();
+        assert!((now - 3600.0 - hour_ago).abs() <= 1.0);
+        assert!((now - parse_bound("now", DisplayZone::Local).unwrap()).abs() <= 1.0);
+        assert_eq!(parse_bound("now-1w", DisplayZone::Local), None);
+        assert_eq!(parse_bound("yesterday", DisplayZone::Local), None);
+    }
+
+    #[test]
+    fn parse_filter_splits_conditions_from_key_text() {
+        let (conditions, rest) = parse_filter("user created_at>=2024-01-31T08:00:00Z updated<2024-02-01T00:00:00Z", DisplayZone::Local);
+        assert_eq!(rest, "user");
+        assert_eq!(conditions.len(), 2);
+        assert_eq!(conditions[0].column, "created_at");
+        assert!(conditions[0].matches(1706688000.0));
+        assert!(!conditions[0].matches(1706687999.0));
+        assert!(conditions[1].matches(1706688000.0));
+        assert!(!conditions[1].matches(1706745600.0));
+    }
+
+    #[test]
+    fn parse_bound_reads_dates_without_offset_in_the_zone() {
+        let berlin = parse_zone("Europe/Berlin").unwrap();
+        let plus_two = parse_zone("+02:00").unwrap();
+        assert_eq!(parse_bound("2024-01-31T10:00:00", berlin), Some(1706691600.0));
+        assert_eq!(parse_bound("2024-01-31T10:00:00", plus_two), Some(1706688000.0));
+        assert_eq!(format_epoch(1706688000.0, plus_two), "2024-01-31T10:00:00+02:00");
+    }
+
+    #[test]
+    fn parse_filter_keeps_plain_text_with_its_spaces() {
+        let (conditions, rest) = parse_filter("a  b>c", DisplayZone::Local);
+        assert!(conditions.is_empty());
+        assert_eq!(rest, "a  b>c");
+    }
+}
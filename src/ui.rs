@@ -1,17 +1,28 @@
-use crate::app::App;
+use crate::app::{App, View, COLUMN_SPACING};
+use crate::admin::{AdminAction, AdminMenu, AdminStage};
+use crate::backup::PathAction;
+use crate::clipboard::CopyTarget;
+use crate::diff::{DiffKind, FieldChange, RecordDiff};
+use crate::hexdump::{HexView, BYTES_PER_LINE};
+use crate::layout::ColumnChooser;
+use crate::profiles::ProfilePicker;
+use crate::sst::display_key;
+use crate::tabs::Tabs;
+use crate::timestamps::TimeFormat;
+use crate::wal::{WalOpKind, WalRow};
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Paragraph, Table},
+    widgets::{block::Title, Bar, BarChart, BarGroup, Block, Borders, List, ListItem, Paragraph, Sparkline, Table},
     Frame,
 };
 
 pub fn ui(f: &mut Frame, app: &mut App) {
-    let footer_bg_color = Color::Blue;
-    let footer_fg_color = Color::Green;
-    let search_color = Color::Magenta;
-    let records_color = Color::Blue;
+    let footer_bg_color = app.theme.footer_bg;
+    let footer_fg_color = app.theme.footer_fg;
+    let search_color = app.theme.search;
+    let records_color = app.theme.accent;
 
     let size = f.size();
     let chunks = Layout::default()
@@ -32,28 +43,237 @@ pub fn ui(f: &mut Frame, app: &mut App) {
             .borders(Borders::ALL)
             .title(title_line));
 
+    if let Some(diff) = &app.record_diff {
+        f.render_widget(input, chunks[1]);
+        draw_record_diff(f, diff, centered_rect(90, 85, size));
+
+        let status_spans = vec![
+            Span::styled(" Ctrl+C", Style::default().fg(Color::Red).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": quit  "),
+            Span::styled("Esc", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": go back  "),
+            Span::styled("Up/Down/PgUp/PgDn/Home", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": scroll")
+        ];
+        let status_line = Paragraph::new(Line::from(status_spans));
+        let status_block = Block::default().style(Style::default().bg(footer_bg_color));
+        f.render_widget(status_line.block(status_block), chunks[4]);
+        return;
+    }
+
+    if let Some(view) = app.hex_view.as_mut() {
+        f.render_widget(input, chunks[1]);
+        draw_hex_view(f, view, centered_rect(80, 80, size));
+
+        let status_spans = vec![
+            Span::styled(" Ctrl+C", Style::default().fg(Color::Red).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": quit  "),
+            Span::styled("Esc", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": go back  "),
+            Span::styled("Shift+arrows/v", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": select  "),
+            Span::styled("/", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": search bytes  "),
+            Span::styled("n/N", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": next/prev match  "),
+            Span::styled("c", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": copy selection")
+        ];
+        let status_line = Paragraph::new(Line::from(status_spans));
+        let status_block = Block::default().style(Style::default().bg(footer_bg_color));
+        f.render_widget(status_line.block(status_block), chunks[4]);
+        return;
+    }
+
     if let Some(raw_data) = &app.show_raw_data {
-        let area = centered_rect(60, 25, size);
-        let popup_block = Block::default().title(Line::from(vec![Span::styled("raw data", Style::default().fg(Color::Magenta))])).borders(Borders::ALL);
-        let paragraph = Paragraph::new(raw_data.as_str())
-            .wrap(ratatui::widgets::Wrap { trim: true })
-            .block(popup_block);
+        let area = if app.raw_maximized {
+            Rect { height: size.height.saturating_sub(1), ..size }
+        } else {
+            centered_rect(60, 25, size)
+        };
+        let mut title_spans = vec![Span::styled(app.popup_title, Style::default().fg(Color::Magenta))];
+        if app.raw_wrap { title_spans.push(Span::raw(" [wrap]")); }
+        if app.raw_maximized { title_spans.push(Span::raw(" [max]")); }
+        let popup_block = Block::default().title(Line::from(title_spans)).borders(Borders::ALL);
+        let inner = popup_block.inner(area);
+
+        let content_height = if app.raw_wrap {
+            let width = inner.width.max(1) as usize;
+            raw_data.lines().map(|l| wrapped_rows(l, width)).sum::<usize>()
+        } else {
+            raw_data.lines().count()
+        };
+        app.raw_page_height = inner.height.max(1);
+        let max_scroll = content_height.saturating_sub(inner.height as usize).min(u16::MAX as usize) as u16;
+        app.raw_scroll = app.raw_scroll.min(max_scroll);
+
+        let mut paragraph = Paragraph::new(raw_data.as_str())
+            .block(popup_block)
+            .scroll((app.raw_scroll, app.raw_scroll_x));
+        if app.raw_wrap {
+            paragraph = paragraph.wrap(ratatui::widgets::Wrap { trim: false });
+        }
         f.render_widget(ratatui::widgets::Clear, area);
         f.render_widget(paragraph, area);
 
-        let status_spans = vec![
+        let mut status_spans = vec![
             Span::styled(" Ctrl+C", Style::default().fg(Color::Red).add_modifier(ratatui::style::Modifier::BOLD)),
             Span::raw(": quit  "),
             Span::styled("Esc", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
-            Span::raw(": go back")
+            Span::raw(": go back  "),
+            Span::styled("Up/Down/PgUp/PgDn", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": scroll  "),
+            Span::styled("w", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": wrap  "),
+            Span::styled("m", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": maximize  "),
+            Span::styled("c", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": copy")
+        ];
+        if let Some(message) = &app.status_message {
+            status_spans.push(Span::styled(format!("  {}", message), Style::default().fg(Color::Yellow)));
+        }
+        let status_line = Paragraph::new(Line::from(status_spans));
+        let status_block = Block::default().style(Style::default().bg(footer_bg_color));
+        f.render_widget(status_line.block(status_block), chunks[4]);
+        return;
+    }
+
+    if app.view == View::Charts {
+        let area = Rect { height: chunks[1].height + chunks[2].height, ..chunks[1] };
+        draw_charts(f, app, area);
+
+        let status_spans = vec![
+            Span::styled(" Ctrl+C", Style::default().fg(Color::Red).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": quit  "),
+            Span::styled("Esc/F3", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": back to records  "),
+            Span::styled("Left/Right", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": key segment  "),
+            Span::styled("+/-", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": group prefix length  "),
+            Span::styled("r", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": rescan")
+        ];
+        let status_line = Paragraph::new(Line::from(status_spans));
+        let status_block = Block::default().style(Style::default().bg(footer_bg_color));
+        f.render_widget(status_line.block(status_block), chunks[4]);
+        return;
+    }
+
+    if app.view == View::Backups {
+        let area = Rect { height: chunks[1].height + chunks[2].height, ..chunks[1] };
+        draw_backups(f, app, area);
+
+        let mut status_spans = vec![
+            Span::styled(" Ctrl+C", Style::default().fg(Color::Red).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": quit  "),
+            Span::styled("Esc/F9", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": back to records  "),
+            Span::styled("n", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": new backup  "),
+            Span::styled("v", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": verify  "),
+            Span::styled("r", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": restore  "),
+            Span::styled("c", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": checkpoint  "),
+            Span::styled("o", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": other backup directory")
+        ];
+        if let Some(message) = &app.status_message {
+            status_spans.push(Span::styled(format!("  {}", message), Style::default().fg(Color::Yellow)));
+        }
+        let status_line = Paragraph::new(Line::from(status_spans));
+        let status_block = Block::default().style(Style::default().bg(footer_bg_color));
+        f.render_widget(status_line.block(status_block), chunks[4]);
+        return;
+    }
+
+    if app.view == View::Diff {
+        let area = Rect { height: chunks[1].height + chunks[2].height, ..chunks[1] };
+        draw_diff(f, app, area);
+
+        let status_spans = vec![
+            Span::styled(" Ctrl+C", Style::default().fg(Color::Red).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": quit  "),
+            Span::styled("Esc/F7", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": browse left database  "),
+            Span::styled("Up/Down/PgUp/PgDn/Home/End", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": navigate  "),
+            Span::styled("f", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": filter")
+        ];
+        let status_line = Paragraph::new(Line::from(status_spans));
+        let status_block = Block::default().style(Style::default().bg(footer_bg_color));
+        f.render_widget(status_line.block(status_block), chunks[4]);
+        return;
+    }
+
+    if app.view == View::Wal {
+        let area = Rect { height: chunks[1].height + chunks[2].height, ..chunks[1] };
+        draw_wal(f, app, area);
+
+        let status_spans = vec![
+            Span::styled(" Ctrl+C", Style::default().fg(Color::Red).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": quit  "),
+            Span::styled("Esc/F5", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": back to records  "),
+            Span::styled("Up/Down/PgUp/PgDn/Home/End", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": navigate  "),
+            Span::styled("Enter", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": show decoded value  "),
+            Span::styled("r", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": reload")
         ];
         let status_line = Paragraph::new(Line::from(status_spans));
         let status_block = Block::default().style(Style::default().bg(footer_bg_color));
-        f.render_widget(status_line.block(status_block), chunks[3]);
+        f.render_widget(status_line.block(status_block), chunks[4]);
+        return;
+    }
+
+    if app.view == View::Sst {
+        let area = Rect { height: chunks[1].height + chunks[2].height, ..chunks[1] };
+        draw_sst_files(f, app, area);
+
+        let status_spans = vec![
+            Span::styled(" Ctrl+C", Style::default().fg(Color::Red).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": quit  "),
+            Span::styled("Esc/F4", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": back to records  "),
+            Span::styled("Up/Down", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": select file  "),
+            Span::styled("r", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": reload")
+        ];
+        let status_line = Paragraph::new(Line::from(status_spans));
+        let status_block = Block::default().style(Style::default().bg(footer_bg_color));
+        f.render_widget(status_line.block(status_block), chunks[4]);
+        return;
+    }
+
+    if app.view == View::Stats {
+        let area = Rect { height: chunks[1].height + chunks[2].height, ..chunks[1] };
+        draw_stats(f, app, area);
+
+        let status_spans = vec![
+            Span::styled(" Ctrl+C", Style::default().fg(Color::Red).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": quit  "),
+            Span::styled("Esc/F2", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": back to records  "),
+            Span::styled("Up/Down", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": column family  "),
+            Span::styled("PgUp/PgDn", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": scroll stats")
+        ];
+        let status_line = Paragraph::new(Line::from(status_spans));
+        let status_block = Block::default().style(Style::default().bg(footer_bg_color));
+        f.render_widget(status_line.block(status_block), chunks[4]);
         return;
     }
 
     f.render_widget(input, chunks[1]);
+    app.detail_x = 0;
 
     if app.focus == crate::app::Focus::TableSelect || (app.focus == crate::app::Focus::Input && app.selected_table.is_none()) {
         let mut types: Vec<String> = app.data_manager.get_records().keys().cloned().collect();
@@ -65,33 +285,95 @@ pub fn ui(f: &mut Frame, app: &mut App) {
             types
         };
 
+        let name_width = filtered_types.iter().map(|t| t.len()).max().unwrap_or(0);
+        let type_stats = &app.data_manager.type_stats;
         let items: Vec<ListItem> = filtered_types.iter().enumerate().map(|(i, t)| {
-            let style = if app.focus == crate::app::Focus::TableSelect && i == app.table_select_index { Style::default().bg(Color::Blue) } else { Style::default() };
-            ListItem::new(t.as_str()).style(style)
+            let style = if app.focus == crate::app::Focus::TableSelect && i == app.table_select_index { Style::default().bg(app.theme.selected) } else { Style::default() };
+            let mut spans = vec![Span::raw(format!("{:<width$}", t, width = name_width))];
+            if let Some(stats) = type_stats.get(t) {
+                spans.push(Span::styled(format!(
+                    "  {:>9} keys   keys {:>10} (avg {:>9})   values {:>10} (avg {:>9})   max {:>10}",
+                    stats.count,
+                    format_bytes(stats.key_bytes),
+                    format_bytes(stats.avg_key_bytes()),
+                    format_bytes(stats.value_bytes),
+                    format_bytes(stats.avg_value_bytes()),
+                    stats.largest.first().map_or("-".to_string(), |(_, size)| format_bytes(*size as u64)),
+                ), Style::default().fg(Color::DarkGray)));
+            }
+            ListItem::new(Line::from(spans)).style(style)
         }).collect();
+
+        let highlighted = filtered_types.get(app.table_select_index).and_then(|t| type_stats.get(t).map(|stats| (t, stats)));
+        let list_area = match highlighted {
+            Some((record_type, stats)) if app.focus == crate::app::Focus::TableSelect && !stats.largest.is_empty() => {
+                let sections = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(1), Constraint::Length(stats.largest.len() as u16 + 2)].as_ref())
+                    .split(chunks[2]);
+                let lines: Vec<Line> = stats.largest.iter().map(|(key, size)| {
+                    Line::from(vec![Span::styled(format!("{:>10}  ", format_bytes(*size as u64)), Style::default().fg(Color::Yellow)), Span::raw(key.as_str())])
+                }).collect();
+                let largest = Paragraph::new(lines)
+                    .block(Block::default().borders(Borders::ALL).title(Line::from(vec![Span::styled(format!("largest values in {}:", record_type), Style::default().fg(records_color))])));
+                f.render_widget(largest, sections[1]);
+                sections[0]
+            }
+            _ => chunks[2],
+        };
         let list = List::new(items)
             .block(Block::default().borders(Borders::ALL).title(Line::from(vec![Span::styled("available record types:", Style::default().fg(records_color))])));
-        f.render_widget(list, chunks[2]);
+        f.render_widget(list, list_area);
     } else {
-    let title = Line::from(vec![Span::styled("records:", Style::default().fg(records_color))]);
-    let block = Block::default().borders(Borders::ALL).title(title);
-    let inner_area = block.inner(chunks[2]);
+    let records_area = if app.detail_panel {
+        let panes = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(100 - app.detail_width), Constraint::Percentage(app.detail_width)].as_ref())
+            .split(chunks[2]);
+        draw_detail_panel(f, app, panes[1]);
+        panes[0]
+    } else {
+        chunks[2]
+    };
+    let mut title_spans = vec![Span::styled("records:", Style::default().fg(records_color))];
+    let inner_area = Block::default().borders(Borders::ALL).inner(records_area);
+    app.table_width = inner_area.width.saturating_sub(2);
+    app.refresh_row_cache();
+    let layout = app.selected_table.as_ref().map(|record_type| app.layout_columns(record_type, app.table_width));
+    if let Some(layout) = &layout {
+        if layout.hidden_left > 0 {
+            title_spans.push(Span::styled(format!(" ◀ {} hidden", layout.hidden_left), Style::default().fg(Color::Yellow)));
+        }
+        if layout.hidden_right > 0 {
+            title_spans.push(Span::styled(format!(" {} hidden ▶", layout.hidden_right), Style::default().fg(Color::Yellow)));
+        }
+    }
+    let block = Block::default().borders(Borders::ALL).title(Line::from(title_spans));
     // Draw a single outer block; the inner area will host the Table without its own block
-    f.render_widget(block, chunks[2]);
+    f.render_widget(block, records_area);
 
-        if let Some(ref record_type) = app.selected_table {
+        if let (Some(record_type), Some(layout)) = (app.selected_table.clone(), layout) {
+            let record_type = &record_type;
             let records = app.get_filtered_records(record_type);
             if !records.is_empty() {
-                let headers = app.data_manager.get_headers().get(record_type).unwrap();
+                let headers = &app.display_headers(record_type);
+                let formats = &app.time_formats(record_type, headers);
+                let cells = app.row_cells(record_type, headers, formats);
 
-                let widths = app.calculate_column_widths(&record_type, inner_area.width.saturating_sub(2));
+                let widths = &layout.widths;
 
                 let rows: Vec<ratatui::widgets::Row> = records.iter().enumerate().map(|(i, r)| {
-                    let style = if app.selected_row == Some(i) { Style::default().bg(Color::Blue) } else { Style::default() };
-                    let cells = r.to_table_row(headers)
-                        .into_iter()
-                        .map(|content| {
-                            ratatui::widgets::Cell::from(content)
+                    let mut style = if app.selected_row == Some(i) { Style::default().bg(app.theme.selected) } else { Style::default() };
+                    if r.is_expired() {
+                        style = style.fg(app.theme.expired).add_modifier(ratatui::style::Modifier::CROSSED_OUT);
+                    }
+                    if app.marked.contains(&r.key) {
+                        style = style.fg(app.theme.marked).add_modifier(ratatui::style::Modifier::BOLD);
+                    }
+                    let row = cells.get(r);
+                    let cells = layout.columns.iter()
+                        .map(|&c| {
+                            ratatui::widgets::Cell::from(row[c].clone())
                         });
                     ratatui::widgets::Row::new(cells).style(style)
                 }).collect();
@@ -112,13 +394,17 @@ pub fn ui(f: &mut Frame, app: &mut App) {
                     .collect();
                 // Render the table directly into the inner area; header occupies the first line
                 let table_area = inner_area;
-                let header_cells = headers.iter().enumerate().map(|(i, h)| {
-                    let mut style = Style::default().fg(Color::Yellow);
+                let header_cells = layout.columns.iter().map(|&i| {
+                    let h = &headers[i];
+                    let mut style = Style::default().fg(app.theme.header);
                     let mut header_text = format!(" {}", h);
-                    if app.sort_column == Some(i) {
+                    if let Some(pos) = app.sort_position(h) {
                         style = style.bg(Color::DarkGray).add_modifier(ratatui::style::Modifier::BOLD);
-                        let arrow = if app.sort_ascending { " ▲ " } else { " ▼ " };
+                        let arrow = if app.sort_keys[pos].ascending { " ▲" } else { " ▼" };
                         header_text.push_str(arrow);
+                        if app.sort_keys.len() > 1 {
+                            header_text.push_str(&(pos + 1).to_string());
+                        }
                     }
                     ratatui::widgets::Cell::from(header_text).style(style)
                 });
@@ -128,7 +414,7 @@ pub fn ui(f: &mut Frame, app: &mut App) {
                 let table = Table::new(visible_rows)
                     .header(header_row)
                     .widths(&constraints)
-                    .column_spacing(3);
+                    .column_spacing(COLUMN_SPACING);
                 f.render_widget(table, table_area);
 
                 let total_pages = app.get_total_pages(record_type, app.rows_per_page as u16);
@@ -162,8 +448,49 @@ pub fn ui(f: &mut Frame, app: &mut App) {
 
     let mut spans = vec![
         Span::styled(" Ctrl+C", Style::default().fg(Color::Red).add_modifier(ratatui::style::Modifier::BOLD)),
-        Span::raw(": quit  ")
+        Span::raw(": quit  "),
+        Span::styled("F2", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+        Span::raw(": stats  "),
+        Span::styled("F3", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+        Span::raw(": charts  "),
+        Span::styled("F4", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+        Span::raw(": SST files  "),
+        Span::styled("F5", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+        Span::raw(": WAL  "),
+        Span::styled("F6", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+        Span::raw(if app.data_manager.is_frozen() { ": unfreeze  " } else { ": freeze  " }),
+        Span::styled("Ctrl+R", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+        Span::raw(": refresh  "),
+        Span::styled("F8", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+        Span::raw(": checkpoint  "),
+        Span::styled("F9", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+        Span::raw(": backups  ")
     ];
+    if let Some(sequence) = app.data_manager.sequence {
+        let (label, color) = if app.data_manager.is_frozen() { ("snapshot", Color::Yellow) } else { ("live", Color::White) };
+        spans.push(Span::styled(format!("[{} @ seq {}]  ", label, sequence), Style::default().fg(color).add_modifier(ratatui::style::Modifier::BOLD)));
+    }
+    if app.writable() {
+        spans.extend(vec![
+            Span::styled("F10", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": admin  ")
+        ]);
+    }
+    if let Some(task) = &app.admin_task {
+        spans.push(Span::styled(
+            match task.progress.load(std::sync::atomic::Ordering::Relaxed) {
+                0 => format!("{} {} ({}s)  ", task.spinner(), task.description, task.started.elapsed().as_secs()),
+                keys => format!("{} {} ({} keys, {}s)  ", task.spinner(), task.description, keys, task.started.elapsed().as_secs()),
+            },
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    if app.diff.is_some() {
+        spans.extend(vec![
+            Span::styled("F7", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::raw(": diff  ")
+        ]);
+    }
 
     match app.focus {
         crate::app::Focus::TableSelect => {
@@ -182,8 +509,20 @@ pub fn ui(f: &mut Frame, app: &mut App) {
                 Span::raw(": go back  "),
                 Span::styled("Tab", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
                 Span::raw(": focus pages  "),
+                Span::styled("Left/Right", Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+                Span::raw(": scroll columns  "),
                 Span::styled("r", Style::default().fg(Color::Blue).add_modifier(ratatui::style::Modifier::BOLD)),
                 Span::raw(": view raw record value  "),
+                Span::styled("c", Style::default().fg(Color::Blue).add_modifier(ratatui::style::Modifier::BOLD)),
+                Span::raw(": copy  "),
+                Span::styled("o", Style::default().fg(Color::Blue).add_modifier(ratatui::style::Modifier::BOLD)),
+                Span::raw(": columns  "),
+                Span::styled("p", Style::default().fg(Color::Blue).add_modifier(ratatui::style::Modifier::BOLD)),
+                Span::raw(if app.detail_panel { ": hide detail  </>: resize  " } else { ": detail panel  " }),
+                Span::styled("m", Style::default().fg(Color::Blue).add_modifier(ratatui::style::Modifier::BOLD)),
+                Span::raw(": mark  "),
+                Span::styled("=", Style::default().fg(Color::Blue).add_modifier(ratatui::style::Modifier::BOLD)),
+                Span::raw(": diff marked  "),
                 Span::styled("d", Style::default().fg(Color::Blue).add_modifier(ratatui::style::Modifier::BOLD)),
                 Span::raw(": delete")
             ]);
@@ -215,10 +554,742 @@ pub fn ui(f: &mut Frame, app: &mut App) {
             ]);
         }
     }
+    if let Some(error) = &app.data_manager.load_error {
+        spans.push(Span::styled(format!("  cannot read the database: {}", error), Style::default().fg(Color::Red)));
+    }
+    if let Some(message) = &app.status_message {
+        spans.push(Span::styled(format!("  {}", message), Style::default().fg(Color::Yellow)));
+    }
     let status_line = Paragraph::new(Line::from(spans));
     let status_block = Block::default()
         .style(Style::default().bg(footer_bg_color));
     f.render_widget(status_line.block(status_block), chunks[4]);
+
+    if let Some(chooser) = &app.column_chooser {
+        draw_column_chooser(f, chooser, centered_rect(50, 60, size));
+    }
+
+    if let Some(menu) = &app.admin_menu {
+        draw_admin_menu(f, menu, centered_rect(50, 40, size));
+    }
+
+    if let Some(picker) = &app.profile_picker {
+        draw_profile_picker(f, picker, centered_rect(60, 50, size));
+    }
+
+    if app.copy_menu {
+        let items: Vec<ListItem> = CopyTarget::ALL.iter().map(|target| {
+            ListItem::new(Line::from(vec![
+                Span::styled(format!(" {} ", target.shortcut()), Style::default().fg(footer_fg_color).add_modifier(ratatui::style::Modifier::BOLD)),
+                Span::raw(target.label()),
+            ]))
+        }).collect();
+        let area = centered_rect(30, 30, size);
+        let area = Rect { height: area.height.min(CopyTarget::ALL.len() as u16 + 2), ..area };
+        let menu = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(Line::from(vec![Span::styled("copy to clipboard", Style::default().fg(Color::Magenta))])));
+        f.render_widget(ratatui::widgets::Clear, area);
+        f.render_widget(menu, area);
+    }
+}
+
+fn draw_stats(f: &mut Frame, app: &mut App, area: Rect) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(Line::from(vec![Span::styled("database stats:", Style::default().fg(Color::Blue))]));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let cfs = match &app.data_manager.stats {
+        None => {
+            f.render_widget(Paragraph::new("collecting properties..."), inner);
+            return;
+        }
+        Some(Err(e)) => {
+            f.render_widget(Paragraph::new(format!("Error reading properties: {}", e)).style(Style::default().fg(Color::Red)), inner);
+            return;
+        }
+        Some(Ok(cfs)) if cfs.is_empty() => {
+            f.render_widget(Paragraph::new("no column families"), inner);
+            return;
+        }
+        Some(Ok(cfs)) => cfs,
+    };
+    app.stats_cf_index = app.stats_cf_index.min(cfs.len() - 1);
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(28), Constraint::Min(1)].as_ref())
+        .split(inner);
+
+    let items: Vec<ListItem> = cfs.iter().enumerate().map(|(i, cf)| {
+        let style = if i == app.stats_cf_index { Style::default().bg(Color::Blue) } else { Style::default() };
+        ListItem::new(Line::from(vec![
+            Span::raw(cf.name.clone()),
+            Span::styled(format!("  ~{}", cf.estimate_num_keys.map_or("?".to_string(), |n| n.to_string())), Style::default().fg(Color::DarkGray)),
+        ])).style(style)
+    }).collect();
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::RIGHT).title(Line::from(vec![Span::styled("column families", Style::default().fg(Color::Yellow))])));
+    f.render_widget(list, columns[0]);
+
+    let cf = &cfs[app.stats_cf_index];
+    let levelstats_height = cf.levelstats.lines().count() as u16;
+    let sections = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(7), Constraint::Length(levelstats_height + 1), Constraint::Min(1)].as_ref())
+        .split(columns[1]);
+
+    let metric = |name: &str, value: String| Line::from(vec![
+        Span::styled(format!(" {:<28}", name), Style::default().fg(Color::Yellow)),
+        Span::raw(value),
+    ]);
+    let bytes = |value: Option<u64>| value.map_or("-".to_string(), format_bytes);
+    let metrics = vec![
+        metric("estimated keys", cf.estimate_num_keys.map_or("-".to_string(), |n| n.to_string())),
+        metric("total SST files size", bytes(cf.total_sst_files_size)),
+        metric("live SST files size", bytes(cf.live_sst_files_size)),
+        metric("active memtable size", bytes(cf.cur_size_all_mem_tables)),
+        metric("all memtables size", bytes(cf.size_all_mem_tables)),
+        metric("block cache usage", bytes(cf.block_cache_usage)),
+    ];
+    f.render_widget(Paragraph::new(metrics).block(Block::default().title(Line::from(vec![Span::styled(format!(" {}", cf.name), Style::default().fg(Color::Magenta))]))), sections[0]);
+    f.render_widget(Paragraph::new(cf.levelstats.as_str()).block(Block::default().title(Line::from(vec![Span::styled(" rocksdb.levelstats", Style::default().fg(Color::Yellow))]))), sections[1]);
+
+    let max_scroll = (cf.stats.lines().count() as u16).saturating_sub(sections[2].height.saturating_sub(1));
+    app.stats_scroll = app.stats_scroll.min(max_scroll);
+    let stats = Paragraph::new(cf.stats.as_str())
+        .scroll((app.stats_scroll, 0))
+        .block(Block::default().title(Line::from(vec![Span::styled(" rocksdb.stats", Style::default().fg(Color::Yellow))])));
+    f.render_widget(stats, sections[2]);
+}
+
+fn draw_admin_menu(f: &mut Frame, menu: &AdminMenu, area: Rect) {
+    let block = Block::default().borders(Borders::ALL).title(Line::from(vec![
+        Span::styled("admin: ", Style::default().fg(Color::Red).add_modifier(ratatui::style::Modifier::BOLD)),
+        Span::raw("column family "),
+        Span::styled(format!("< {} >", menu.cf()), Style::default().fg(Color::Yellow)),
+    ]));
+    let inner = block.inner(area);
+    f.render_widget(ratatui::widgets::Clear, area);
+    f.render_widget(block, area);
+
+    let lines: Vec<Line> = match &menu.stage {
+        AdminStage::Menu => {
+            let mut lines: Vec<Line> = AdminAction::ALL.iter().enumerate().map(|(i, action)| {
+                let style = if i == menu.action_index { Style::default().bg(Color::Blue) } else { Style::default() };
+                Line::from(Span::styled(format!(" {} ", action.label()), style))
+            }).collect();
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled("Up/Down: action  Left/Right: column family  Enter: choose  Esc: close", Style::default().fg(Color::DarkGray))));
+            lines
+        }
+        AdminStage::Input { action, fields, field } => {
+            let mut lines = vec![Line::from(Span::styled(action.label(), Style::default().add_modifier(ratatui::style::Modifier::BOLD))), Line::from("")];
+            for (i, (label, value)) in fields.iter().enumerate() {
+                let style = if i == *field { Style::default().fg(Color::Yellow) } else { Style::default() };
+                lines.push(Line::from(vec![Span::styled(format!("{}: ", label), style), Span::raw(value.clone())]));
+            }
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled("Tab: next field  Enter: continue  Esc: back", Style::default().fg(Color::DarkGray))));
+            lines
+        }
+        AdminStage::Confirm(op) => vec![
+            Line::from(format!("Really {}?", op.describe())),
+            Line::from(""),
+            Line::from(vec![
+                Span::styled("y", Style::default().fg(Color::Red).add_modifier(ratatui::style::Modifier::BOLD)),
+                Span::raw(": yes  "),
+                Span::styled("n/Esc", Style::default().fg(Color::Green).add_modifier(ratatui::style::Modifier::BOLD)),
+                Span::raw(": no"),
+            ]),
+        ],
+    };
+    f.render_widget(Paragraph::new(lines).wrap(ratatui::widgets::Wrap { trim: false }), inner);
+}
+
+/// The tab bar above the search input: every tab's database and table, then the active tab's path and mode.
+pub fn draw_tab_bar(f: &mut Frame, tabs: &Tabs, area: Rect) {
+    let active = tabs.active();
+    let mut spans = Vec::new();
+    for (i, app) in tabs.apps.iter().enumerate() {
+        let style = if i == tabs.active {
+            Style::default().fg(Color::Black).bg(active.theme.accent).add_modifier(ratatui::style::Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::Gray)
+        };
+        spans.push(Span::styled(format!(" {} {} ", i + 1, app.tab_label()), style));
+        spans.push(Span::raw(" "));
+    }
+    spans.push(Span::raw(active.db_path.clone()));
+    let mode = active.overrides.mode;
+    if mode != crate::options::OpenMode::ReadOnly {
+        spans.push(Span::styled(format!("  [{}]", mode.label()), Style::default().fg(Color::Yellow)));
+    }
+    spans.push(Span::styled("  Ctrl+O: open  Ctrl+T: new tab  Alt+Left/Right: switch", Style::default().fg(Color::DarkGray)));
+    f.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
+/// The selected row's decoded value beside the table, following the cursor.
+fn draw_detail_panel(f: &mut Frame, app: &mut App, area: Rect) {
+    app.detail_x = area.x;
+    let record = app.selected_record();
+    let key = record.as_ref().map(|record| record.key.clone());
+    if key != app.detail_key {
+        app.detail_key = key;
+        app.detail_scroll = 0;
+    }
+    let mut title_spans = vec![Span::styled("detail", Style::default().fg(Color::Magenta))];
+    let mut lines = Vec::new();
+    if let Some(record) = &record {
+        title_spans.push(Span::raw(format!(" {}", record.key)));
+        if let Some(written_at) = record.written_at {
+            lines.push(Line::from(Span::styled(format!("written {}", crate::timestamps::format_epoch(written_at as f64, app.zone)), Style::default().fg(Color::DarkGray))));
+        }
+        if let Some(expires_at) = record.expires_at {
+            lines.push(Line::from(Span::styled(format!("expires {}", crate::timestamps::format_epoch(expires_at as f64, app.zone)), Style::default().fg(Color::DarkGray))));
+        }
+        lines.extend(record.pretty_value().lines().map(|line| Line::from(line.to_string())));
+    }
+    let block = Block::default().borders(Borders::ALL).title(Line::from(title_spans));
+    let inner = block.inner(area);
+    let width = inner.width.max(1) as usize;
+    let content_height = lines.iter()
+        .map(|line| wrapped_rows(&line.spans.iter().map(|span| span.content.as_ref()).collect::<String>(), width))
+        .sum::<usize>();
+    let max_scroll = content_height.saturating_sub(inner.height as usize).min(u16::MAX as usize) as u16;
+    app.detail_scroll = app.detail_scroll.min(max_scroll);
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(ratatui::widgets::Wrap { trim: false })
+        .scroll((app.detail_scroll, 0));
+    f.render_widget(paragraph, area);
+}
+
+fn draw_profile_picker(f: &mut Frame, picker: &ProfilePicker, area: Rect) {
+    let block = Block::default().borders(Borders::ALL).title(Line::from(vec![
+        Span::styled("open database", Style::default().fg(Color::Magenta)),
+    ]));
+    let inner = block.inner(area);
+    f.render_widget(ratatui::widgets::Clear, area);
+    f.render_widget(block, area);
+
+    let sections = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)].as_ref())
+        .split(inner);
+
+    let name_width = picker.entries.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    let mut items: Vec<ListItem> = picker.entries.iter().map(|(name, description)| {
+        ListItem::new(Line::from(vec![
+            Span::styled(format!(" {:<width$}  ", name, width = name_width), Style::default().add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::styled(description.clone(), Style::default().fg(Color::DarkGray)),
+        ]))
+    }).collect();
+    items.push(ListItem::new(Line::from(Span::styled(" open a path...", Style::default().fg(Color::Yellow)))));
+    let list = List::new(items).highlight_style(Style::default().bg(Color::Blue));
+    let mut state = ratatui::widgets::ListState::default().with_selected(Some(picker.index));
+    f.render_stateful_widget(list, sections[0], &mut state);
+
+    let hint = match &picker.path_input {
+        Some(input) => Line::from(vec![Span::styled("path: ", Style::default().fg(Color::Magenta)), Span::raw(input.as_str())]),
+        None if picker.startup => Line::from(Span::styled("Up/Down: choose  Enter: open  Esc: quit", Style::default().fg(Color::Yellow))),
+        None => Line::from(Span::styled("Up/Down: choose  Enter: open  Esc: close", Style::default().fg(Color::Yellow))),
+    };
+    f.render_widget(Paragraph::new(hint), sections[1]);
+}
+
+fn draw_backups(f: &mut Frame, app: &mut App, area: Rect) {
+    let Some(backups) = app.backups.as_ref() else { return };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(if backups.prompt.is_some() { 3 } else { 0 })])
+        .split(area);
+    let block = Block::default().borders(Borders::ALL).title(Line::from(vec![
+        Span::styled("backups in ", Style::default().fg(Color::Blue)),
+        Span::raw(backups.backup_dir.clone()),
+    ]));
+    match &backups.backups {
+        Err(e) => f.render_widget(Paragraph::new(format!("Error reading backups: {}", e)).style(Style::default().fg(Color::Red)).block(block), chunks[0]),
+        Ok(list) if list.is_empty() => f.render_widget(Paragraph::new("no backups yet; press n to create one").block(block), chunks[0]),
+        Ok(list) => {
+            let header = ratatui::widgets::Row::new(["id", "created", "size", "files"])
+                .style(Style::default().fg(Color::Yellow).add_modifier(ratatui::style::Modifier::BOLD));
+            let rows: Vec<ratatui::widgets::Row> = list.iter().map(|backup| ratatui::widgets::Row::new(vec![
+                backup.backup_id.to_string(),
+                crate::backup::format_timestamp(backup.timestamp),
+                format_bytes(backup.size),
+                backup.num_files.to_string(),
+            ])).collect();
+            let table = Table::new(rows)
+                .header(header)
+                .widths(&[Constraint::Length(6), Constraint::Length(20), Constraint::Length(12), Constraint::Length(8)])
+                .column_spacing(COLUMN_SPACING)
+                .block(block)
+                .highlight_style(Style::default().bg(Color::Blue));
+            let mut state = ratatui::widgets::TableState::default().with_selected(Some(backups.index));
+            f.render_stateful_widget(table, chunks[0], &mut state);
+        }
+    }
+    if let Some((action, input)) = &backups.prompt {
+        let title = match action {
+            PathAction::Checkpoint => "create checkpoint in (Enter to confirm, Esc to cancel)".to_string(),
+            PathAction::Restore(id) => format!("restore backup {} into (Enter to confirm, Esc to cancel)", id),
+            PathAction::BackupDir => "backup directory (Enter to confirm, Esc to cancel)".to_string(),
+        };
+        f.render_widget(Paragraph::new(input.as_str()).block(Block::default().borders(Borders::ALL).title(title)), chunks[1]);
+    }
+}
+
+fn diff_color(kind: DiffKind) -> Color {
+    match kind {
+        DiffKind::OnlyLeft => Color::Red,
+        DiffKind::OnlyRight => Color::Green,
+        DiffKind::Changed => Color::Yellow,
+    }
+}
+
+fn draw_diff(f: &mut Frame, app: &mut App, area: Rect) {
+    let Some(diff) = app.diff.as_mut() else { return };
+    let visible = diff.visible().len();
+    diff.index = diff.index.min(visible.saturating_sub(1));
+    let selected = diff.selected().cloned();
+    let changes = selected.as_ref().map(|e| e.field_changes(&diff.rule_set)).unwrap_or_default();
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage(40),
+            Constraint::Length(changes.len().clamp(1, 8) as u16 + 2),
+            Constraint::Min(3),
+        ])
+        .split(area);
+
+    let count = |kind| diff.entries.iter().filter(|e| e.kind == kind).count();
+    let filter = match diff.filter {
+        None => "all",
+        Some(DiffKind::OnlyLeft) => "only left",
+        Some(DiffKind::OnlyRight) => "only right",
+        Some(DiffKind::Changed) => "changed",
+    };
+    let title = Line::from(vec![
+        Span::styled(format!("{} ↔ {}: ", diff.left_path, diff.right_path), Style::default().fg(Color::Blue)),
+        Span::styled(format!("-{} ", count(DiffKind::OnlyLeft)), Style::default().fg(Color::Red)),
+        Span::styled(format!("+{} ", count(DiffKind::OnlyRight)), Style::default().fg(Color::Green)),
+        Span::styled(format!("~{} ", count(DiffKind::Changed)), Style::default().fg(Color::Yellow)),
+        Span::styled(format!("[{}]", filter), Style::default().fg(Color::DarkGray)),
+    ]);
+    let mut block = Block::default().borders(Borders::ALL).title(title);
+    if !diff.only_left_cfs.is_empty() || !diff.only_right_cfs.is_empty() {
+        let cfs = diff.only_left_cfs.iter().map(|cf| Span::styled(format!(" -{}", cf), Style::default().fg(Color::Red)))
+            .chain(diff.only_right_cfs.iter().map(|cf| Span::styled(format!(" +{}", cf), Style::default().fg(Color::Green))));
+        let line = Line::from(std::iter::once(Span::raw("column families:")).chain(cfs).collect::<Vec<_>>());
+        block = block.title(Title::from(line).alignment(Alignment::Right));
+    }
+    if visible == 0 {
+        let text = if diff.entries.is_empty() { "the shared column families are identical" } else { "no differences of this kind" };
+        f.render_widget(Paragraph::new(text).block(block), chunks[0]);
+    } else {
+        let items: Vec<ListItem> = diff.visible().iter().map(|entry| {
+            ListItem::new(Line::from(Span::styled(format!("{} {}", entry.kind.marker(), entry.label()), Style::default().fg(diff_color(entry.kind)))))
+        }).collect();
+        let list = List::new(items).block(block).highlight_style(Style::default().bg(Color::Blue));
+        let mut state = ratatui::widgets::ListState::default().with_selected(Some(diff.index));
+        f.render_stateful_widget(list, chunks[0], &mut state);
+    }
+
+    draw_field_changes(f, &changes, chunks[1]);
+
+    let side = |value: Option<&Vec<u8>>| match (value, &selected) {
+        (Some(value), Some(entry)) => text_lines(&crate::models::deserialize_record(&diff.rule_set, &entry.key, value).pretty_value()),
+        (None, Some(_)) => vec![Line::from("(missing)")],
+        _ => Vec::new(),
+    };
+    let left = side(selected.as_ref().and_then(|e| e.left.as_ref()));
+    let right = side(selected.as_ref().and_then(|e| e.right.as_ref()));
+    draw_side_by_side(f, ("left".to_string(), left), ("right".to_string(), right), 0, chunks[2]);
+}
+
+fn text_lines(text: &str) -> Vec<Line<'static>> {
+    text.lines().map(|line| Line::from(line.to_string())).collect()
+}
+
+fn draw_field_changes(f: &mut Frame, changes: &[FieldChange], area: Rect) {
+    let lines: Vec<Line> = changes.iter().map(|change| Line::from(vec![
+        Span::styled(format!("{}: ", change.path), Style::default().fg(Color::Yellow)),
+        Span::styled(crate::diff::field_value(&change.left), Style::default().fg(Color::Red)),
+        Span::raw(" → "),
+        Span::styled(crate::diff::field_value(&change.right), Style::default().fg(Color::Green)),
+    ])).collect();
+    f.render_widget(Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("changed fields")), area);
+}
+
+fn draw_side_by_side(f: &mut Frame, left: (String, Vec<Line>), right: (String, Vec<Line>), scroll: u16, area: Rect) {
+    let sides = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(area);
+    for ((title, lines), area) in [(left, sides[0]), (right, sides[1])] {
+        let paragraph = Paragraph::new(lines).scroll((scroll, 0)).block(Block::default().borders(Borders::ALL).title(title));
+        f.render_widget(paragraph, area);
+    }
+}
+
+/// Hex dump lines of `data` with the bytes that differ from `other` highlighted.
+fn hex_diff_lines(data: &[u8], other: &[u8], color: Color) -> Vec<Line<'static>> {
+    data.chunks(BYTES_PER_LINE).enumerate().map(|(line, bytes)| {
+        let mut spans = vec![Span::styled(format!("{:08x}  ", line * BYTES_PER_LINE), Style::default().fg(Color::DarkGray))];
+        for (i, byte) in bytes.iter().enumerate() {
+            let offset = line * BYTES_PER_LINE + i;
+            let style = if other.get(offset) == Some(byte) { Style::default() } else { Style::default().fg(color).add_modifier(ratatui::style::Modifier::BOLD) };
+            spans.push(Span::styled(format!("{:02x}", byte), style));
+            spans.push(Span::raw(if i == 7 { "  " } else { " " }));
+        }
+        Line::from(spans)
+    }).collect()
+}
+
+fn draw_record_diff(f: &mut Frame, diff: &RecordDiff, area: Rect) {
+    let block = Block::default()
+        .title(Line::from(vec![Span::styled(format!("diff: {} ↔ {}", diff.left.key, diff.right.key), Style::default().fg(Color::Magenta))]))
+        .borders(Borders::ALL);
+    let inner = block.inner(area);
+    f.render_widget(ratatui::widgets::Clear, area);
+    f.render_widget(block, area);
+
+    let left_title = format!("{} ({} bytes)", diff.left.key, diff.left.raw_data.len());
+    let right_title = format!("{} ({} bytes)", diff.right.key, diff.right.raw_data.len());
+    match &diff.changes {
+        Some(changes) => {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(changes.len().clamp(1, 10) as u16 + 2), Constraint::Min(3)])
+                .split(inner);
+            if changes.is_empty() {
+                f.render_widget(Paragraph::new("the decoded values are identical").block(Block::default().borders(Borders::ALL).title("changed fields")), chunks[0]);
+            } else {
+                draw_field_changes(f, changes, chunks[0]);
+            }
+            draw_side_by_side(f, (left_title, text_lines(&diff.left.pretty_value())), (right_title, text_lines(&diff.right.pretty_value())), diff.scroll, chunks[1]);
+        }
+        None => {
+            let left = hex_diff_lines(&diff.left.raw_data, &diff.right.raw_data, Color::Red);
+            let right = hex_diff_lines(&diff.right.raw_data, &diff.left.raw_data, Color::Green);
+            draw_side_by_side(f, (format!("{} — not JSON, byte diff", left_title), left), (right_title, right), diff.scroll, inner);
+        }
+    }
+}
+
+fn draw_wal(f: &mut Frame, app: &mut App, area: Rect) {
+    let block = Block::default().borders(Borders::ALL).title(Line::from(vec![Span::styled("write-ahead log:", Style::default().fg(Color::Blue))]));
+    let files = match &app.wal {
+        Some(Ok(files)) => files,
+        Some(Err(e)) => {
+            f.render_widget(Paragraph::new(format!("Error reading WAL files: {}", e)).style(Style::default().fg(Color::Red)).block(block), area);
+            return;
+        }
+        None => return,
+    };
+    let rows = crate::wal::rows(files);
+    if rows.is_empty() {
+        f.render_widget(Paragraph::new("no WAL files (all writes have been flushed to SST files)").block(block), area);
+        return;
+    }
+    app.wal_index = app.wal_index.min(rows.len() - 1);
+
+    let items: Vec<ListItem> = rows.iter().map(|row| match *row {
+        WalRow::File(f) => {
+            let file = &files[f];
+            let mut spans = vec![Span::styled(
+                format!("{}  {}  {} batches", file.name, format_bytes(file.size), file.batches.len()),
+                Style::default().fg(Color::Magenta).add_modifier(ratatui::style::Modifier::BOLD),
+            )];
+            if let Some(error) = &file.error {
+                spans.push(Span::styled(format!("  stopped reading: {}", error), Style::default().fg(Color::Red)));
+            }
+            ListItem::new(Line::from(spans))
+        }
+        WalRow::Batch(f, b) => {
+            let batch = &files[f].batches[b];
+            ListItem::new(Line::from(Span::styled(
+                format!("  batch seq {}  ({} ops)", batch.sequence, batch.count),
+                Style::default().fg(Color::Yellow),
+            )))
+        }
+        WalRow::Op(f, b, o) => {
+            let batch = &files[f].batches[b];
+            let op = &batch.ops[o];
+            let key = String::from_utf8_lossy(&op.key).to_string();
+            let sequence = batch.op_sequences()[o].map_or(String::new(), |s| s.to_string());
+            let mut spans = vec![
+                Span::styled(format!("    {:>10}  ", sequence), Style::default().fg(Color::DarkGray)),
+                Span::styled(format!("{:<13}", op.kind.label()), Style::default().fg(Color::Green)),
+            ];
+            if op.column_family != 0 {
+                spans.push(Span::styled(format!("cf {}  ", op.column_family), Style::default().fg(Color::DarkGray)));
+            }
+            spans.push(Span::raw(key.clone()));
+            if let Some(value) = &op.value {
+                let shown = match op.kind {
+                    WalOpKind::RangeDelete => format!(" .. {}", String::from_utf8_lossy(value)),
+                    _ => format!("  {}", crate::models::deserialize_record(&app.rule_set, &key, value).data),
+                };
+                spans.push(Span::styled(shown, Style::default().fg(Color::DarkGray)));
+            }
+            ListItem::new(Line::from(spans))
+        }
+    }).collect();
+    let list = List::new(items).block(block).highlight_style(Style::default().bg(Color::Blue));
+    let mut state = ratatui::widgets::ListState::default().with_selected(Some(app.wal_index));
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_sst_files(f: &mut Frame, app: &mut App, area: Rect) {
+    let files = match &app.sst_files {
+        Some(Ok(files)) => files,
+        Some(Err(e)) => {
+            let block = Block::default().borders(Borders::ALL).title(Line::from(vec![Span::styled("SST files:", Style::default().fg(Color::Blue))]));
+            f.render_widget(Paragraph::new(format!("Error listing live files: {}", e)).style(Style::default().fg(Color::Red)).block(block), area);
+            return;
+        }
+        None => return,
+    };
+    let total: usize = files.iter().map(|file| file.size).sum();
+    let title = format!("SST files: {} live, {}", files.len(), format_bytes(total as u64));
+    let block = Block::default().borders(Borders::ALL).title(Line::from(vec![Span::styled(title, Style::default().fg(Color::Blue))]));
+
+    app.sst_index = app.sst_index.min(files.len().saturating_sub(1));
+    let header = ratatui::widgets::Row::new(["column family", "file", "level", "size", "entries", "deletions", "smallest key", "largest key"])
+        .style(Style::default().fg(Color::Yellow));
+    let rows: Vec<ratatui::widgets::Row> = files.iter().map(|file| {
+        ratatui::widgets::Row::new(vec![
+            file.column_family_name.clone(),
+            file.name.trim_start_matches('/').to_string(),
+            file.level.to_string(),
+            format_bytes(file.size as u64),
+            file.num_entries.to_string(),
+            file.num_deletions.to_string(),
+            display_key(&file.start_key),
+            display_key(&file.end_key),
+        ])
+    }).collect();
+    let widths = [
+        Constraint::Length(16),
+        Constraint::Length(12),
+        Constraint::Length(5),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Length(9),
+        Constraint::Percentage(50),
+        Constraint::Percentage(50),
+    ];
+    let table = Table::new(rows)
+        .header(header)
+        .block(block)
+        .widths(&widths)
+        .column_spacing(2)
+        .highlight_style(Style::default().bg(Color::Blue));
+    let mut state = ratatui::widgets::TableState::default().with_selected(Some(app.sst_index));
+    f.render_stateful_widget(table, area, &mut state);
+}
+
+fn draw_charts(f: &mut Frame, app: &App, area: Rect) {
+    let Some(charts) = &app.charts else { return };
+    let query = &charts.query;
+    let mut title = vec![Span::styled(format!("charts: {}", query.record_type), Style::default().fg(Color::Blue))];
+    if let Some(Ok(data)) = &charts.data {
+        title.push(Span::raw(format!(" ({} keys scanned)", data.scanned)));
+    }
+    if charts.is_loading() {
+        title.push(Span::styled(" scanning...", Style::default().fg(Color::Yellow)));
+    }
+    let block = Block::default().borders(Borders::ALL).title(Line::from(title));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let data = match &charts.data {
+        None => return,
+        Some(Err(e)) => {
+            f.render_widget(Paragraph::new(format!("Error scanning database: {}", e)).style(Style::default().fg(Color::Red)), inner);
+            return;
+        }
+        Some(Ok(data)) => data,
+    };
+
+    let sections = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(45), Constraint::Length(6), Constraint::Min(5)].as_ref())
+        .split(inner);
+
+    let bar_width = |count: usize, width: u16| ((width.saturating_sub(2) as usize / count.max(1)).saturating_sub(1)).clamp(3, 12) as u16;
+
+    let size_bars: Vec<Bar> = data.size_buckets.iter().map(|(bound, count)| {
+        Bar::default().value(*count).label(Line::from(format!("≤{}", short_bytes(*bound))))
+    }).collect();
+    let sizes = BarChart::default()
+        .block(Block::default().borders(Borders::ALL).title(Line::from(vec![Span::styled("value sizes", Style::default().fg(Color::Yellow))])))
+        .data(BarGroup::default().bars(&size_bars))
+        .bar_width(bar_width(size_bars.len(), sections[0].width))
+        .bar_gap(1)
+        .bar_style(Style::default().fg(Color::Blue))
+        .value_style(Style::default().fg(Color::Black).bg(Color::Blue));
+    f.render_widget(sizes, sections[0]);
+
+    let prefix = if query.prefix_len > 0 { format!(", first {} chars", query.prefix_len) } else { String::new() };
+    let counts: Vec<u64> = data.segment_counts.iter().map(|(_, count)| *count).collect();
+    let spark = Sparkline::default()
+        .block(Block::default().borders(Borders::ALL).title(Line::from(vec![Span::styled(
+            format!("keys per segment {}{} in key order: {} groups", query.segment, prefix, counts.len()),
+            Style::default().fg(Color::Yellow),
+        )])))
+        .data(&counts)
+        .style(Style::default().fg(Color::Magenta));
+    f.render_widget(spark, sections[1]);
+
+    let mut top: Vec<&(String, u64)> = data.segment_counts.iter().collect();
+    top.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    let group_width = top.iter().map(|(name, _)| name.chars().count()).max().unwrap_or(3).clamp(3, 12) as u16;
+    let fit = (sections[2].width.saturating_sub(2) / (group_width + 1)).max(1) as usize;
+    let group_bars: Vec<Bar> = top.iter().take(fit).map(|(name, count)| {
+        Bar::default().value(*count).label(Line::from(name.clone()))
+    }).collect();
+    let groups = BarChart::default()
+        .block(Block::default().borders(Borders::ALL).title(Line::from(vec![Span::styled("busiest groups", Style::default().fg(Color::Yellow))])))
+        .data(BarGroup::default().bars(&group_bars))
+        .bar_width(group_width)
+        .bar_gap(1)
+        .bar_style(Style::default().fg(Color::Magenta))
+        .value_style(Style::default().fg(Color::Black).bg(Color::Magenta));
+    f.render_widget(groups, sections[2]);
+}
+
+fn short_bytes(bytes: u64) -> String {
+    match bytes {
+        b if b >= 1 << 30 => format!("{}G", b >> 30),
+        b if b >= 1 << 20 => format!("{}M", b >> 20),
+        b if b >= 1 << 10 => format!("{}K", b >> 10),
+        b => format!("{}B", b),
+    }
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 { format!("{} B", bytes) } else { format!("{:.1} {}", value, UNITS[unit]) }
+}
+
+fn draw_column_chooser(f: &mut Frame, chooser: &ColumnChooser, area: Rect) {
+    let title = format!("columns: {}", chooser.record_type);
+    let block = Block::default()
+        .title(Line::from(vec![Span::styled(title, Style::default().fg(Color::Magenta))]))
+        .borders(Borders::ALL);
+    let inner = block.inner(area);
+    f.render_widget(ratatui::widgets::Clear, area);
+    f.render_widget(block, area);
+
+    let sections = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)].as_ref())
+        .split(inner);
+
+    let items: Vec<ListItem> = chooser.columns.iter().enumerate().map(|(i, column)| {
+        let check = if column.visible { "[x]" } else { "[ ]" };
+        let width = column.width.map(|w| w.to_string()).unwrap_or_else(|| "auto".to_string());
+        let time = match column.time {
+            TimeFormat::Auto => String::new(),
+            format => format!("  time: {}", format.label()),
+        };
+        let style = if i == chooser.index { Style::default().bg(Color::Blue) } else { Style::default() };
+        ListItem::new(Line::from(vec![
+            Span::raw(format!("{} {}", check, column.name)),
+            Span::styled(format!("  width: {}{}", width, time), Style::default().fg(Color::DarkGray)),
+        ])).style(style)
+    }).collect();
+    let mut state = ratatui::widgets::ListState::default().with_selected(Some(chooser.index));
+    f.render_stateful_widget(List::new(items), sections[0], &mut state);
+
+    let hint = match &chooser.width_input {
+        Some(input) => Line::from(vec![Span::styled("width (empty = auto): ", Style::default().fg(Color::Magenta)), Span::raw(input.as_str())]),
+        None => Line::from(Span::styled("Space: show/hide  Shift+Up/Down or K/J: move  w: width  t: time unit  Enter: save", Style::default().fg(Color::Yellow))),
+    };
+    f.render_widget(Paragraph::new(hint), sections[1]);
+}
+
+fn draw_hex_view(f: &mut Frame, view: &mut HexView, area: Rect) {
+    let title = format!("hex: {} ({} bytes)", view.title, view.data.len());
+    let block = Block::default()
+        .title(Line::from(vec![Span::styled(title, Style::default().fg(Color::Magenta))]))
+        .borders(Borders::ALL);
+    let inner = block.inner(area);
+    f.render_widget(ratatui::widgets::Clear, area);
+    f.render_widget(block, area);
+
+    let sections = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1), Constraint::Length(7)].as_ref())
+        .split(inner);
+    view.page_lines = sections[0].height.max(1) as usize;
+
+    let byte_style = |offset: usize| {
+        if offset == view.cursor {
+            Style::default().bg(Color::Blue)
+        } else if view.is_selected(offset) {
+            Style::default().bg(Color::DarkGray)
+        } else if view.is_match(offset) {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default()
+        }
+    };
+
+    let end = (view.scroll + view.page_lines).min(view.total_lines());
+    let lines: Vec<Line> = (view.scroll..end).map(|line| {
+        let (offset_text, bytes) = view.format_line(line);
+        let mut spans = vec![Span::styled(offset_text, Style::default().fg(Color::DarkGray)), Span::raw("  ")];
+        for (i, byte) in bytes.iter().enumerate() {
+            let offset = line * BYTES_PER_LINE + i;
+            match byte {
+                Some(b) => spans.push(Span::styled(format!("{:02x}", b), byte_style(offset))),
+                None => spans.push(Span::raw("  ")),
+            }
+            spans.push(Span::raw(if i == 7 { "  " } else { " " }));
+        }
+        spans.push(Span::styled(" |", Style::default().fg(Color::DarkGray)));
+        for (i, byte) in bytes.iter().enumerate() {
+            if let Some(b) = byte {
+                let c = if b.is_ascii_graphic() || *b == b' ' { *b as char } else { '.' };
+                spans.push(Span::styled(c.to_string(), byte_style(line * BYTES_PER_LINE + i)));
+            }
+        }
+        spans.push(Span::styled("|", Style::default().fg(Color::DarkGray)));
+        Line::from(spans)
+    }).collect();
+    f.render_widget(Paragraph::new(lines), sections[0]);
+
+    let (start, end) = view.selection();
+    let prompt = if let Some(input) = &view.search_input {
+        Line::from(vec![Span::styled("search (hex or \"text\"): ", Style::default().fg(Color::Magenta)), Span::raw(input.as_str())])
+    } else {
+        let mut spans = vec![Span::styled(
+            format!("offset 0x{:x} ({})  selection {}..={} ({} bytes)", view.cursor, view.cursor, start, end, end + 1 - start),
+            Style::default().fg(Color::Yellow),
+        )];
+        if let Some(message) = &view.message {
+            spans.push(Span::raw(format!("  {}", message)));
+        }
+        Line::from(spans)
+    };
+    f.render_widget(Paragraph::new(prompt), sections[1]);
+
+    let inspector: Vec<Line> = view.interpretations().into_iter().map(|(name, value)| {
+        Line::from(vec![Span::styled(format!("{:<8}", name), Style::default().fg(Color::Yellow)), Span::raw(value)])
+    }).collect();
+    f.render_widget(Paragraph::new(inspector).block(Block::default().borders(Borders::TOP)), sections[2]);
 }
 
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
@@ -239,4 +1310,80 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
             Constraint::Percentage((100 - percent_x) / 2),
         ])
         .split(popup_layout[1])[1]
-}
\ No newline at end of file
+}
+/// Rows `line` takes in a paragraph wrapped with `Wrap { trim: false }` into `width` columns.
+/// Follows ratatui's word wrapper so that scrolling stops at the last rendered row.
+fn wrapped_rows(line: &str, width: usize) -> usize {
+    let max = width.max(1);
+    let (mut rows, mut line_len, mut line_width) = (0, 0, 0);
+    let (mut word_len, mut word_width) = (0, 0);
+    let (mut whitespaces, mut whitespace_width) = (std::collections::VecDeque::new(), 0);
+    let mut seen_word = false;
+    for c in line.chars() {
+        let is_whitespace = c.is_whitespace() && c != '\u{a0}';
+        let char_width = Span::raw(&*c.encode_utf8(&mut [0; 4])).width();
+        if char_width > max {
+            continue;
+        }
+        if seen_word && is_whitespace || line_len == 0 && word_width + whitespace_width + char_width > max {
+            line_len += whitespaces.len() + word_len;
+            line_width += whitespace_width + word_width;
+            whitespaces.clear();
+            (whitespace_width, word_len, word_width) = (0, 0, 0);
+        }
+        if line_width >= max || line_width + whitespace_width + word_width >= max && char_width > 0 {
+            let mut remaining = max.saturating_sub(line_width);
+            rows += 1;
+            (line_len, line_width) = (0, 0);
+            let mut first = whitespaces.pop_front();
+            while let Some(space_width) = first {
+                whitespace_width -= space_width;
+                if space_width > remaining {
+                    break;
+                }
+                remaining -= space_width;
+                first = whitespaces.pop_front();
+            }
+            if is_whitespace && first.is_none() {
+                continue;
+            }
+        }
+        if is_whitespace {
+            whitespace_width += char_width;
+            whitespaces.push_back(char_width);
+        } else {
+            word_width += char_width;
+            word_len += 1;
+        }
+        seen_word = !is_whitespace;
+    }
+    if word_len > 0 || !whitespaces.is_empty() {
+        if line_len == 0 && word_len == 0 {
+            rows += 1;
+        } else {
+            line_len += whitespaces.len();
+        }
+        line_len += word_len;
+    }
+    if line_len > 0 {
+        rows += 1;
+    }
+    rows.max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrapped_rows_moves_whole_words() {
+        assert_eq!(wrapped_rows("aaaa bbbb cc", 6), 3);
+        assert_eq!(wrapped_rows("abcdefghij", 4), 3);
+        assert_eq!(wrapped_rows("", 10), 1);
+    }
+
+    #[test]
+    fn wrapped_rows_counts_display_width() {
+        assert_eq!(wrapped_rows("日本語テキスト", 4), 4);
+    }
+}
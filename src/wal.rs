@@ -0,0 +1,247 @@
+use std::path::Path;
+
+const BLOCK_SIZE: usize = 32 * 1024;
+const HEADER_SIZE: usize = 7;
+const RECYCLABLE_HEADER_SIZE: usize = 11;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WalOpKind {
+    Put,
+    Delete,
+    SingleDelete,
+    Merge,
+    RangeDelete,
+    LogData,
+    BlobIndex,
+    WideColumns,
+    Marker(&'static str),
+}
+
+impl WalOpKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            WalOpKind::Put => "put",
+            WalOpKind::Delete => "delete",
+            WalOpKind::SingleDelete => "single-delete",
+            WalOpKind::Merge => "merge",
+            WalOpKind::RangeDelete => "range-delete",
+            WalOpKind::LogData => "log-data",
+            WalOpKind::BlobIndex => "blob-index",
+            WalOpKind::WideColumns => "wide-columns",
+            WalOpKind::Marker(name) => name,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct WalOp {
+    pub kind: WalOpKind,
+    pub column_family: u32,
+    pub key: Vec<u8>,
+    /// The value for puts and merges, the end key for range deletes.
+    pub value: Option<Vec<u8>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct WalBatch {
+    pub sequence: u64,
+    pub count: u32,
+    pub ops: Vec<WalOp>,
+}
+
+impl WalBatch {
+    /// Sequence number of each op; markers and log data do not consume one.
+    pub fn op_sequences(&self) -> Vec<Option<u64>> {
+        let mut next = self.sequence;
+        self.ops.iter().map(|op| match op.kind {
+            WalOpKind::Marker(_) | WalOpKind::LogData => None,
+            _ => {
+                next += 1;
+                Some(next - 1)
+            }
+        }).collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct WalFile {
+    pub name: String,
+    pub size: u64,
+    pub batches: Vec<WalBatch>,
+    /// Set when the file could not be read to the end, e.g. a torn write after a crash.
+    pub error: Option<String>,
+}
+
+/// One line of the WAL browser: a file, a batch within it, or an op within the batch.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WalRow {
+    File(usize),
+    Batch(usize, usize),
+    Op(usize, usize, usize),
+}
+
+pub fn rows(files: &[WalFile]) -> Vec<WalRow> {
+    let mut rows = Vec::new();
+    for (f, file) in files.iter().enumerate() {
+        rows.push(WalRow::File(f));
+        for (b, batch) in file.batches.iter().enumerate() {
+            rows.push(WalRow::Batch(f, b));
+            rows.extend((0..batch.ops.len()).map(|o| WalRow::Op(f, b, o)));
+        }
+    }
+    rows
+}
+
+/// Reads every `*.log` file in the database directory, oldest first.
+pub fn read_wal_files(db_path: &str) -> Result<Vec<WalFile>, String> {
+    let mut paths: Vec<_> = std::fs::read_dir(db_path)
+        .map_err(|e| e.to_string())?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "log"))
+        .collect();
+    paths.sort();
+    Ok(paths.iter().map(|path| read_wal_file(path)).collect())
+}
+
+pub fn read_wal_file(path: &Path) -> WalFile {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let data = match std::fs::read(path) {
+        Ok(data) => data,
+        Err(e) => return WalFile { name, size: 0, batches: Vec::new(), error: Some(e.to_string()) },
+    };
+    let mut batches = Vec::new();
+    let error = read_records(&data, |record| {
+        batches.push(parse_batch(record)?);
+        Ok(())
+    }).err();
+    WalFile { name, size: data.len() as u64, batches, error }
+}
+
+/// Splits the log into its logical records, reassembling fragments that span blocks.
+fn read_records(data: &[u8], mut on_record: impl FnMut(&[u8]) -> Result<(), String>) -> Result<(), String> {
+    let mut pos = 0;
+    let mut pending: Option<Vec<u8>> = None;
+    while pos < data.len() {
+        let block_left = BLOCK_SIZE - pos % BLOCK_SIZE;
+        if block_left < HEADER_SIZE {
+            pos += block_left;
+            continue;
+        }
+        let header = data.get(pos..pos + HEADER_SIZE).ok_or_else(|| format!("truncated header at offset {}", pos))?;
+        let checksum = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+        let length = u16::from_le_bytes([header[4], header[5]]) as usize;
+        let record_type = header[6];
+        if record_type == 0 && length == 0 {
+            // Zero-filled padding or preallocated space at the end of the file
+            pos += block_left;
+            continue;
+        }
+        let header_size = if (5..=8).contains(&record_type) { RECYCLABLE_HEADER_SIZE } else { HEADER_SIZE };
+        let payload = data.get(pos + header_size..pos + header_size + length)
+            .ok_or_else(|| format!("truncated record at offset {}", pos))?;
+        if unmask_crc(checksum) != crc32c(&data[pos + 6..pos + header_size + length]) {
+            return Err(format!("checksum mismatch at offset {}", pos));
+        }
+        pos += header_size + length;
+
+        match record_type {
+            1 | 5 => on_record(payload)?,
+            2 | 6 => pending = Some(payload.to_vec()),
+            3 | 7 => pending.as_mut().ok_or("middle fragment without a first fragment")?.extend_from_slice(payload),
+            4 | 8 => {
+                let mut record = pending.take().ok_or("last fragment without a first fragment")?;
+                record.extend_from_slice(payload);
+                on_record(&record)?;
+            }
+            // Metadata records such as the compression type; they carry no writes
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn parse_batch(data: &[u8]) -> Result<WalBatch, String> {
+    if data.len() < 12 {
+        return Err("write batch shorter than its header".to_string());
+    }
+    let sequence = u64::from_le_bytes(data[0..8].try_into().unwrap());
+    let count = u32::from_le_bytes(data[8..12].try_into().unwrap());
+    let mut reader = Reader { data: &data[12..] };
+    let mut ops = Vec::new();
+    while let Some(tag) = reader.byte() {
+        let op = |kind, column_family, key, value| WalOp { kind, column_family, key, value };
+        ops.push(match tag {
+            0x00 => op(WalOpKind::Delete, 0, reader.slice()?, None),
+            0x01 => op(WalOpKind::Put, 0, reader.slice()?, Some(reader.slice()?)),
+            0x02 => op(WalOpKind::Merge, 0, reader.slice()?, Some(reader.slice()?)),
+            0x03 => op(WalOpKind::LogData, 0, reader.slice()?, None),
+            0x04 => op(WalOpKind::Delete, reader.varint()?, reader.slice()?, None),
+            0x05 => op(WalOpKind::Put, reader.varint()?, reader.slice()?, Some(reader.slice()?)),
+            0x06 => op(WalOpKind::Merge, reader.varint()?, reader.slice()?, Some(reader.slice()?)),
+            0x07 => op(WalOpKind::SingleDelete, 0, reader.slice()?, None),
+            0x08 => op(WalOpKind::SingleDelete, reader.varint()?, reader.slice()?, None),
+            0x09 | 0x12 | 0x13 => op(WalOpKind::Marker("begin-prepare"), 0, Vec::new(), None),
+            0x0A => op(WalOpKind::Marker("end-prepare"), 0, reader.slice()?, None),
+            0x0B => op(WalOpKind::Marker("commit"), 0, reader.slice()?, None),
+            0x0C => op(WalOpKind::Marker("rollback"), 0, reader.slice()?, None),
+            0x0D => op(WalOpKind::Marker("noop"), 0, Vec::new(), None),
+            0x0E => op(WalOpKind::RangeDelete, reader.varint()?, reader.slice()?, Some(reader.slice()?)),
+            0x0F => op(WalOpKind::RangeDelete, 0, reader.slice()?, Some(reader.slice()?)),
+            0x10 => op(WalOpKind::BlobIndex, reader.varint()?, reader.slice()?, Some(reader.slice()?)),
+            0x11 => op(WalOpKind::BlobIndex, 0, reader.slice()?, Some(reader.slice()?)),
+            0x14 => op(WalOpKind::Delete, 0, reader.slice()?, None),
+            0x15 => {
+                let _timestamp = reader.slice()?;
+                op(WalOpKind::Marker("commit"), 0, reader.slice()?, None)
+            }
+            0x16 => op(WalOpKind::WideColumns, 0, reader.slice()?, Some(reader.slice()?)),
+            0x17 => op(WalOpKind::WideColumns, reader.varint()?, reader.slice()?, Some(reader.slice()?)),
+            other => return Err(format!("unknown write batch tag 0x{:02x} in batch {}", other, sequence)),
+        });
+    }
+    Ok(WalBatch { sequence, count, ops })
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn byte(&mut self) -> Option<u8> {
+        let (&first, rest) = self.data.split_first()?;
+        self.data = rest;
+        Some(first)
+    }
+
+    fn varint(&mut self) -> Result<u32, String> {
+        let (value, len) = crate::hexdump::decode_varint(self.data).ok_or("truncated varint")?;
+        self.data = &self.data[len..];
+        u32::try_from(value).map_err(|_| "varint out of range".to_string())
+    }
+
+    fn slice(&mut self) -> Result<Vec<u8>, String> {
+        let len = self.varint()? as usize;
+        if self.data.len() < len {
+            return Err("truncated write batch entry".to_string());
+        }
+        let (value, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(value.to_vec())
+    }
+}
+
+fn unmask_crc(masked: u32) -> u32 {
+    let rot = masked.wrapping_sub(0xa282_ead8);
+    rot.rotate_left(15)
+}
+
+fn crc32c(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0x82f6_3b78 } else { crc >> 1 };
+        }
+    }
+    !crc
+}